@@ -0,0 +1,199 @@
+use std::{fs::File, path::PathBuf};
+
+use arrow::csv::WriterBuilder;
+use clap::ValueEnum;
+
+use crate::{
+    inspect::open_builder,
+    peak::batch_to_rows,
+    utils::{markdown_table, nested_as_text},
+};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum TableStyle {
+    /// +---+ borders, safe for any terminal
+    Ascii,
+    /// Box-drawing borders
+    Unicode,
+    /// GitHub-flavored Markdown
+    Markdown,
+    /// Tab-separated, no padding, for piping into other tools
+    Tsv,
+}
+
+struct Borders {
+    horizontal: char,
+    vertical: char,
+    top: [char; 3],
+    middle: [char; 3],
+    bottom: [char; 3],
+}
+
+const ASCII: Borders = Borders {
+    horizontal: '-',
+    vertical: '|',
+    top: ['+', '+', '+'],
+    middle: ['+', '+', '+'],
+    bottom: ['+', '+', '+'],
+};
+
+const UNICODE: Borders = Borders {
+    horizontal: '─',
+    vertical: '│',
+    top: ['┌', '┬', '┐'],
+    middle: ['├', '┼', '┤'],
+    bottom: ['└', '┴', '┘'],
+};
+
+fn boxed_table(borders: &Borders, header: &[String], rows: &[Vec<String>]) -> String {
+    let clean = |cell: &str| cell.replace('\n', " ");
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(clean(cell).chars().count());
+        }
+    }
+
+    let rule = |[left, cross, right]: [char; 3]| {
+        let segments: Vec<String> = widths
+            .iter()
+            .map(|w| borders.horizontal.to_string().repeat(w + 2))
+            .collect();
+        format!("{}{}{}\n", left, segments.join(&cross.to_string()), right)
+    };
+    let line = |cells: &[String]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, w)| format!(" {:<w$} ", clean(cell), w = *w))
+            .collect();
+        let v = borders.vertical.to_string();
+        format!("{}{}{}\n", v, padded.join(&v), v)
+    };
+
+    let mut out = rule(borders.top);
+    out.push_str(&line(header));
+    out.push_str(&rule(borders.middle));
+    for row in rows {
+        out.push_str(&line(row));
+    }
+    out.push_str(&rule(borders.bottom));
+
+    out
+}
+
+fn tsv_table(header: &[String], rows: &[Vec<String>]) -> String {
+    let clean = |cell: &String| cell.replace(['\t', '\n'], " ");
+
+    std::iter::once(header)
+        .chain(rows.iter().map(Vec::as_slice))
+        .map(|cells| cells.iter().map(clean).collect::<Vec<_>>().join("\t") + "\n")
+        .collect()
+}
+
+/// Renders rows for non-interactive output in the chosen style.
+pub fn format_table(style: TableStyle, header: &[String], rows: &[Vec<String>]) -> String {
+    match style {
+        TableStyle::Ascii => boxed_table(&ASCII, header, rows),
+        TableStyle::Unicode => boxed_table(&UNICODE, header, rows),
+        TableStyle::Markdown => markdown_table(header, rows),
+        TableStyle::Tsv => tsv_table(header, rows),
+    }
+}
+
+/// Prints the first `n` rows, reading a single batch of that size.
+pub fn head(path: &PathBuf, n: usize, style: TableStyle) -> Result<(), Box<dyn std::error::Error>> {
+    let builder = open_builder(path)?;
+    let header: Vec<String> = builder
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| f.name().to_owned())
+        .collect();
+
+    let mut reader = builder.with_batch_size(n.max(1)).with_limit(n).build()?;
+    let rows = match reader.next() {
+        Some(batch) => batch_to_rows(&batch?),
+        None => Vec::new(),
+    };
+
+    print!("{}", format_table(style, &header, &rows));
+
+    Ok(())
+}
+
+/// Prints the last `n` rows. Only the row groups that overlap the tail are
+/// read, and rows before the cut-off within the first of them are skipped.
+pub fn tail(path: &PathBuf, n: usize, style: TableStyle) -> Result<(), Box<dyn std::error::Error>> {
+    let builder = open_builder(path)?;
+    let header: Vec<String> = builder
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| f.name().to_owned())
+        .collect();
+
+    let row_groups = builder.metadata().row_groups();
+    let total_rows: usize = row_groups.iter().map(|rg| rg.num_rows() as usize).sum();
+    let start = total_rows.saturating_sub(n);
+
+    let mut selected = Vec::new();
+    let mut first_group_start = None;
+    let mut group_start = 0;
+    for (idx, rg) in row_groups.iter().enumerate() {
+        let group_end = group_start + rg.num_rows() as usize;
+        if group_end > start {
+            selected.push(idx);
+            first_group_start.get_or_insert(group_start);
+        }
+        group_start = group_end;
+    }
+
+    let skip = start - first_group_start.unwrap_or(start);
+    let reader = builder
+        .with_row_groups(selected)
+        .with_offset(skip)
+        .build()?;
+
+    let mut rows = Vec::new();
+    for batch in reader {
+        rows.extend(batch_to_rows(&batch?));
+    }
+
+    print!("{}", format_table(style, &header, &rows));
+
+    Ok(())
+}
+
+/// Streams the whole file to CSV one batch at a time, so memory use stays
+/// bounded by the batch size. The summary goes to stderr unless `quiet`.
+pub fn to_csv(
+    input: &PathBuf,
+    output: &PathBuf,
+    delimiter: char,
+    header: bool,
+    quiet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !delimiter.is_ascii() {
+        return Err(format!("delimiter '{}' must be a single ASCII character", delimiter).into());
+    }
+
+    let reader = open_builder(input)?.build()?;
+    let mut writer = WriterBuilder::new()
+        .with_delimiter(delimiter as u8)
+        .with_header(header)
+        .build(File::create(output)?);
+
+    let mut rows = 0;
+    for batch in reader {
+        let batch = batch?;
+        rows += batch.num_rows();
+        writer.write(&nested_as_text(&batch)?)?;
+    }
+
+    if !quiet {
+        eprintln!("Wrote {} rows to {}", rows, output.display());
+    }
+
+    Ok(())
+}
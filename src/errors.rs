@@ -1,14 +1,24 @@
 use core::fmt;
 
 pub enum PeakError {
+    /// Not a file the viewer or editor can open.
     UnsupportedFileType,
+    /// Not a Parquet file, for commands that read the footer or write Parquet.
+    ParquetOnly,
 }
 
 impl fmt::Display for PeakError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            PeakError::UnsupportedFileType => {
-                write!(f, "UNSUPPORTED_FILE_TYPE (.parquet or .pqt only)")
+            PeakError::UnsupportedFileType => write!(
+                f,
+                "UNSUPPORTED_FILE_TYPE (.parquet, .pqt, .csv, .json, .ndjson or .jsonl)"
+            ),
+            PeakError::ParquetOnly => {
+                write!(
+                    f,
+                    "UNSUPPORTED_FILE_TYPE (this command reads .parquet or .pqt only)"
+                )
             }
         }
     }
@@ -0,0 +1,253 @@
+use crate::prune::CmpOp;
+
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+pub enum Expr {
+    Number(f64),
+    Column(usize),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, BinOp, Box<Expr>),
+}
+
+/// A comparison between two arithmetic expressions, e.g. `amount > 100`.
+pub struct Condition {
+    lhs: Expr,
+    op: CmpOp,
+    rhs: Expr,
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while let Some(&d) = chars.peek() {
+                if d.is_ascii_digit() || d == '.' {
+                    number.push(d);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let value = number
+                .parse()
+                .map_err(|_| format!("invalid number '{}'", number))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while let Some(&d) = chars.peek() {
+                if d.is_alphanumeric() || d == '_' {
+                    ident.push(d);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Ident(ident));
+        } else if c == '"' {
+            chars.next();
+            let mut ident = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(d) => ident.push(d),
+                    None => return Err("unterminated quoted column name".to_string()),
+                }
+            }
+            tokens.push(Token::Ident(ident));
+        } else if "+-*/()".contains(c) {
+            tokens.push(Token::Op(c));
+            chars.next();
+        } else {
+            return Err(format!("unexpected character '{}'", c));
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    columns: &'a [String],
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek() {
+            let op = if *op == '+' { BinOp::Add } else { BinOp::Sub };
+            self.pos += 1;
+            let rhs = self.term()?;
+            lhs = Expr::Binary(Box::new(lhs), op, Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.factor()?;
+        while let Some(Token::Op(op @ ('*' | '/'))) = self.peek() {
+            let op = if *op == '*' { BinOp::Mul } else { BinOp::Div };
+            self.pos += 1;
+            let rhs = self.factor()?;
+            lhs = Expr::Binary(Box::new(lhs), op, Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn factor(&mut self) -> Result<Expr, String> {
+        match self.advance() {
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::Ident(name)) => self
+                .columns
+                .iter()
+                .position(|c| *c == name)
+                .map(Expr::Column)
+                .ok_or_else(|| format!("unknown column '{}'", name)),
+            Some(Token::Op('-')) => Ok(Expr::Neg(Box::new(self.factor()?))),
+            Some(Token::Op('(')) => {
+                let inner = self.expr()?;
+                match self.advance() {
+                    Some(Token::Op(')')) => Ok(inner),
+                    _ => Err("missing ')'".to_string()),
+                }
+            }
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+/// Parses arithmetic over column names and numeric constants, e.g.
+/// `price * qty + 1`. Column names with spaces can be double-quoted.
+pub fn parse(input: &str, columns: &[String]) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+        columns,
+    };
+
+    let expr = parser.expr()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(format!("unexpected {:?}", token)),
+    }
+}
+
+/// Splits a `name = expression` definition and parses the right-hand side.
+pub fn parse_definition(input: &str, columns: &[String]) -> Result<(String, Expr), String> {
+    let (name, body) = input
+        .split_once('=')
+        .ok_or("expected 'name = expression'")?;
+
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("computed column needs a name".to_string());
+    }
+
+    Ok((name.to_string(), parse(body, columns)?))
+}
+
+/// Splits at the first comparison operator outside double quotes and
+/// parses both sides as arithmetic.
+pub fn parse_condition(input: &str, columns: &[String]) -> Result<Condition, String> {
+    let mut in_quotes = false;
+    let at = input
+        .char_indices()
+        .find(|&(_, c)| {
+            if c == '"' {
+                in_quotes = !in_quotes;
+            }
+            !in_quotes && "<>=!".contains(c)
+        })
+        .map(|(i, _)| i)
+        .ok_or("expected a comparison: = != < <= > >=")?;
+
+    let (lhs, rest) = input.split_at(at);
+    let (op, rhs) =
+        CmpOp::strip_prefix(rest).ok_or_else(|| format!("unknown operator in '{}'", input))?;
+
+    Ok(Condition {
+        lhs: parse(lhs, columns)?,
+        op,
+        rhs: parse(rhs, columns)?,
+    })
+}
+
+impl Condition {
+    pub fn eval(&self, row: &[String], nulls: &[bool]) -> Option<bool> {
+        let (l, r) = (self.lhs.eval(row, nulls)?, self.rhs.eval(row, nulls)?);
+        Some(match self.op {
+            CmpOp::Eq => l == r,
+            CmpOp::NotEq => l != r,
+            CmpOp::Lt => l < r,
+            CmpOp::LtEq => l <= r,
+            CmpOp::Gt => l > r,
+            CmpOp::GtEq => l >= r,
+        })
+    }
+
+    /// The mask cell for a row: ✓ or ✗, or `None` when either side is null.
+    pub fn eval_to_string(&self, row: &[String], nulls: &[bool]) -> Option<String> {
+        self.eval(row, nulls)
+            .map(|matched| if matched { "✓" } else { "✗" }.to_string())
+    }
+}
+
+impl Expr {
+    /// Evaluates against one row of rendered cells, with `nulls` marking the
+    /// cells that are null. Nulls and non-numeric cells make the whole
+    /// result null.
+    pub fn eval(&self, row: &[String], nulls: &[bool]) -> Option<f64> {
+        match self {
+            Expr::Number(value) => Some(*value),
+            Expr::Column(idx) => {
+                if nulls.get(*idx).copied().unwrap_or(true) {
+                    return None;
+                }
+                row.get(*idx)?.trim().parse().ok()
+            }
+            Expr::Neg(inner) => inner.eval(row, nulls).map(|v| -v),
+            Expr::Binary(lhs, op, rhs) => {
+                let (l, r) = (lhs.eval(row, nulls)?, rhs.eval(row, nulls)?);
+                match op {
+                    BinOp::Add => Some(l + r),
+                    BinOp::Sub => Some(l - r),
+                    BinOp::Mul => Some(l * r),
+                    BinOp::Div if r == 0.0 => None,
+                    BinOp::Div => Some(l / r),
+                }
+            }
+        }
+    }
+
+    pub fn eval_to_string(&self, row: &[String], nulls: &[bool]) -> Option<String> {
+        self.eval(row, nulls).map(|v| v.to_string())
+    }
+}
@@ -0,0 +1,170 @@
+use std::{fs::File, path::PathBuf, process::exit};
+
+use parquet::{
+    arrow::arrow_reader::ParquetRecordBatchReaderBuilder, schema::printer::print_parquet_metadata,
+};
+
+use crate::{
+    errors::PeakError,
+    utils::{all_null_columns, describe_fields, format_bytes, validate_extension},
+};
+
+pub fn open_builder(
+    path: &PathBuf,
+) -> Result<ParquetRecordBatchReaderBuilder<File>, Box<dyn std::error::Error>> {
+    if !validate_extension(path) {
+        eprintln!("ERROR: {}", PeakError::ParquetOnly);
+        exit(1)
+    }
+
+    let file = File::open(path)?;
+    Ok(ParquetRecordBatchReaderBuilder::try_new(file)?)
+}
+
+const HEAT_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// One heatmap cell: `.` for no nulls, `?` when the writer recorded no
+/// null count, otherwise a bar whose height tracks the null fraction.
+fn heat_cell(nulls: Option<u64>, rows: i64) -> char {
+    match nulls {
+        None => '?',
+        Some(0) => '.',
+        Some(n) => {
+            let fraction = n as f64 / rows.max(1) as f64;
+            let level = (fraction * HEAT_LEVELS.len() as f64).ceil() as usize;
+            HEAT_LEVELS[level.clamp(1, HEAT_LEVELS.len()) - 1]
+        }
+    }
+}
+
+/// Per-column null counts from the row group statistics, with one heatmap
+/// cell per row group so sparse regions of the file stand out.
+pub fn stats(path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let builder = open_builder(path)?;
+    let metadata = builder.metadata();
+    let columns = metadata.file_metadata().schema_descr().columns();
+    let total_rows = metadata.file_metadata().num_rows();
+
+    let names: Vec<String> = columns.iter().map(|c| c.path().string()).collect();
+    let width = names
+        .iter()
+        .map(|n| n.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("column".len());
+
+    println!(
+        "{} rows in {} row groups",
+        total_rows,
+        metadata.num_row_groups()
+    );
+    println!(
+        "{:<width$}  {:>12}  {:>7}  row groups (. none, ▁-█ share of rows null, ? unknown)",
+        "column",
+        "nulls",
+        "null %",
+        width = width
+    );
+
+    for (idx, name) in names.iter().enumerate() {
+        let mut total_nulls: Option<u64> = Some(0);
+        let mut heatmap = String::new();
+
+        for rg in metadata.row_groups() {
+            let nulls = rg.column(idx).statistics().and_then(|s| s.null_count_opt());
+            total_nulls = total_nulls.zip(nulls).map(|(a, b)| a + b);
+            heatmap.push(heat_cell(nulls, rg.num_rows()));
+        }
+
+        let (count, percent) = match total_nulls {
+            Some(n) => (
+                n.to_string(),
+                format!("{:.1}%", n as f64 * 100.0 / total_rows.max(1) as f64),
+            ),
+            None => ("?".to_string(), "?".to_string()),
+        };
+
+        println!(
+            "{:<width$}  {:>12}  {:>7}  {}",
+            name,
+            count,
+            percent,
+            heatmap,
+            width = width
+        );
+    }
+
+    let all_null = all_null_columns(metadata);
+    if !all_null.is_empty() {
+        println!();
+        println!("fully null columns:");
+        for name in all_null {
+            println!("  {}", name);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn schema(path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let builder = open_builder(path)?;
+
+    for line in describe_fields(builder.schema()) {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+/// File-level metadata as `key: value` lines, one row group or column per
+/// line so the output greps well.
+pub fn meta(path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let builder = open_builder(path)?;
+    let metadata = builder.metadata();
+    let file_metadata = metadata.file_metadata();
+
+    println!(
+        "created_by: {}",
+        file_metadata.created_by().unwrap_or("unknown")
+    );
+    println!("rows: {}", file_metadata.num_rows());
+    println!("row_groups: {}", metadata.num_row_groups());
+
+    for (idx, rg) in metadata.row_groups().iter().enumerate() {
+        println!(
+            "row_group {}: rows={} bytes={} ({}) compressed={} ({})",
+            idx,
+            rg.num_rows(),
+            rg.total_byte_size(),
+            format_bytes(rg.total_byte_size() as usize),
+            rg.compressed_size(),
+            format_bytes(rg.compressed_size() as usize),
+        );
+    }
+
+    for (idx, column) in file_metadata.schema_descr().columns().iter().enumerate() {
+        let mut codecs: Vec<String> = Vec::new();
+        for rg in metadata.row_groups() {
+            let codec = rg.column(idx).compression().to_string();
+            if !codecs.contains(&codec) {
+                codecs.push(codec);
+            }
+        }
+        println!(
+            "column {}: compression={}",
+            column.path().string(),
+            codecs.join(",")
+        );
+    }
+
+    Ok(())
+}
+
+pub fn footer(path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let builder = open_builder(path)?;
+
+    let mut out = std::io::stdout().lock();
+    print_parquet_metadata(&mut out, builder.metadata());
+
+    Ok(())
+}
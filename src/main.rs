@@ -1,44 +1,396 @@
-use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
+use arrow::datatypes::{DataType, TimeUnit};
+use clap::{CommandFactory, Parser, Subcommand, error::ErrorKind};
+use dump::TableStyle;
+use peak::PeakOptions;
+use prune::RowGroupFilter;
+use query::QueryFormat;
+
+mod dump;
 mod errors;
+mod expr;
+mod inspect;
+mod merge;
 mod peak;
+mod prune;
+mod query;
+mod source;
 mod sql_editor;
 mod table;
 mod utils;
+mod validate;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+
+    /// Print only the requested data; skip status lines such as "Wrote N rows"
+    #[arg(long, global = true)]
+    quiet: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     Peak {
-        /// File to peek at
-        file: std::path::PathBuf,
+        /// File to peek at (defaults to $PQ_PEAK_FILE)
+        file: Option<std::path::PathBuf>,
         
         /// Number of rows to load per batch (default: 100)
-        #[arg(short, long, default_value_t = 100)]
+        #[arg(short, long, default_value_t = 100, value_parser = parse_positive)]
         batch_size: usize,
+
+        /// Only read the first N columns (for very wide files)
+        #[arg(long, value_parser = parse_positive)]
+        max_columns: Option<usize>,
+
+        /// Only read these columns, e.g. "id,name,created_at"
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<String>,
+
+        /// Read batches with the async Parquet reader (helps on slow/network storage)
+        #[arg(long)]
+        async_reader: bool,
+
+        /// Only read the first row group, for an instant look at huge files
+        #[arg(long)]
+        fast: bool,
+
+        /// Cast a column after reading, e.g. "ts:timestamp_ms" (repeatable)
+        #[arg(long, value_parser = parse_cast)]
+        cast: Vec<(String, DataType)>,
+
+        /// Only open row groups whose min/max statistics can match, e.g. "date>=2024-01-01"
+        #[arg(long, value_parser = prune::parse_rg_filter)]
+        rg_filter: Option<RowGroupFilter>,
+
+        /// Share the terminal width across visible columns by content length
+        #[arg(long = "limit-columns-width-total")]
+        fill_width: bool,
+
+        /// Show running totals of compressed and decompressed bytes read
+        #[arg(long)]
+        debug_io: bool,
+
+        /// Open with this zero-based row selected
+        #[arg(long)]
+        select_row: Option<usize>,
+
+        /// Show dictionary-encoded values with their index, e.g. "3:Active"
+        #[arg(long)]
+        dict_keys: bool,
+
+        /// Shorten list, struct and map values to this many characters
+        #[arg(long)]
+        max_nested_chars: Option<usize>,
+
+        /// Text to draw for null cells instead of NULL, e.g. "∅"
+        #[arg(long)]
+        null_text: Option<String>,
+
+        /// Keep the first column on screen while scrolling right (toggle with p)
+        #[arg(long)]
+        freeze_first: bool,
+
+        /// Start in the record view, one `column: value` line per column
+        #[arg(long)]
+        transpose: bool,
     },
     Edit {
-        /// File to edit with SQL
-        file: std::path::PathBuf,
+        /// File to edit with SQL (defaults to $PQ_PEAK_FILE)
+        file: Option<std::path::PathBuf>,
         
         /// Number of rows to load per batch (default: 100)
-        #[arg(short, long, default_value_t = 100)]
+        #[arg(short, long, default_value_t = 100, value_parser = parse_positive)]
         batch_size: usize,
+
+        /// Load the editor with the query in this .sql file
+        #[arg(long)]
+        sql_file: Option<std::path::PathBuf>,
+
+        /// Maximum rows per row group in files saved with Ctrl+S
+        #[arg(long)]
+        row_group_size: Option<usize>,
+
+        /// LIMIT added to SELECT queries that don't have one
+        #[arg(long, default_value_t = sql_editor::MAX_PREVIEW_ROWS, value_parser = parse_positive)]
+        preview_rows: usize,
+
+        /// Run queries as written, without adding a preview LIMIT
+        #[arg(long)]
+        no_auto_limit: bool,
+    },
+    /// Run a SQL query against the file (as table `data`) and print the result
+    Query {
+        /// File to query
+        file: std::path::PathBuf,
+
+        /// SQL to run, e.g. "SELECT count(*) FROM data"
+        sql: String,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = QueryFormat::Table)]
+        format: QueryFormat,
+
+        /// How to draw the table when --format is table
+        #[arg(long, value_enum, default_value_t = TableStyle::Ascii)]
+        table_style: TableStyle,
+
+        /// Exit with an error if the query returns no rows
+        #[arg(long)]
+        fail_on_empty: bool,
+    },
+    /// Print each column's name, Arrow type and nullability
+    Schema {
+        /// File to read the schema from
+        file: std::path::PathBuf,
+    },
+    /// Print row counts, row group sizes, codecs and the writer string
+    Meta {
+        /// File to read metadata from
+        file: std::path::PathBuf,
+    },
+    /// Check that a file is readable and, optionally, that it is sorted as declared
+    Validate {
+        /// File to validate
+        file: std::path::PathBuf,
+
+        /// Scan the sorting columns declared in each row group and verify the order
+        #[arg(long)]
+        check_sort: bool,
     },
+    /// Print the decoded Parquet footer (schema, row groups, column chunks)
+    Footer {
+        /// File to read the footer from
+        file: std::path::PathBuf,
+    },
+    /// Show per-column null counts and a per-row-group null heatmap
+    Stats {
+        /// File to read statistics from
+        file: std::path::PathBuf,
+    },
+    /// Print the first rows of a file without opening the viewer
+    Head {
+        /// File to read
+        file: std::path::PathBuf,
+
+        /// Number of rows to print
+        #[arg(short = 'n', long, default_value_t = 10)]
+        rows: usize,
+
+        /// How to draw the table
+        #[arg(long, value_enum, default_value_t = TableStyle::Ascii)]
+        table_style: TableStyle,
+    },
+    /// Print the last rows of a file without opening the viewer
+    Tail {
+        /// File to read
+        file: std::path::PathBuf,
+
+        /// Number of rows to print
+        #[arg(short = 'n', long, default_value_t = 10)]
+        rows: usize,
+
+        /// How to draw the table
+        #[arg(long, value_enum, default_value_t = TableStyle::Ascii)]
+        table_style: TableStyle,
+    },
+    /// Convert a whole Parquet file to CSV
+    ToCsv {
+        /// Parquet file to convert
+        input: std::path::PathBuf,
+
+        /// CSV file to write
+        output: std::path::PathBuf,
+
+        /// Field delimiter
+        #[arg(long, default_value_t = ',')]
+        delimiter: char,
+
+        /// Don't write a header row
+        #[arg(long)]
+        no_header: bool,
+    },
+    /// Concatenate Parquet files with identical schemas into one file
+    Merge {
+        /// File to write the merged data to
+        output: std::path::PathBuf,
+
+        /// Files to merge, in order
+        #[arg(required = true)]
+        inputs: Vec<std::path::PathBuf>,
+    },
+}
+
+const DEFAULT_FILE_ENV: &str = "PQ_PEAK_FILE";
+
+fn resolve_file(file: &Option<PathBuf>) -> PathBuf {
+    if let Some(file) = file {
+        return file.clone();
+    }
+
+    match std::env::var_os(DEFAULT_FILE_ENV) {
+        Some(file) if !file.is_empty() => PathBuf::from(file),
+        _ => Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                format!("no file given and {} is not set", DEFAULT_FILE_ENV),
+            )
+            .exit(),
+    }
+}
+
+/// Counts where zero would leave nothing to show, such as a batch size.
+fn parse_positive(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(0) => Err("must be at least 1".to_string()),
+        Ok(n) => Ok(n),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Parses a `column:type` cast spec. Types use short names so they are easy
+/// to type on the command line.
+fn parse_cast(spec: &str) -> Result<(String, DataType), String> {
+    let (column, type_name) = spec
+        .rsplit_once(':')
+        .ok_or("expected COLUMN:TYPE, e.g. ts:timestamp_ms")?;
+
+    let data_type = match type_name.trim().to_lowercase().as_str() {
+        "bool" | "boolean" => DataType::Boolean,
+        "int8" => DataType::Int8,
+        "int16" => DataType::Int16,
+        "int32" => DataType::Int32,
+        "int64" => DataType::Int64,
+        "uint8" => DataType::UInt8,
+        "uint16" => DataType::UInt16,
+        "uint32" => DataType::UInt32,
+        "uint64" => DataType::UInt64,
+        "float32" => DataType::Float32,
+        "float64" => DataType::Float64,
+        "utf8" | "string" => DataType::Utf8,
+        "date32" | "date" => DataType::Date32,
+        "date64" => DataType::Date64,
+        "timestamp_s" => DataType::Timestamp(TimeUnit::Second, None),
+        "timestamp_ms" => DataType::Timestamp(TimeUnit::Millisecond, None),
+        "timestamp_us" => DataType::Timestamp(TimeUnit::Microsecond, None),
+        "timestamp_ns" => DataType::Timestamp(TimeUnit::Nanosecond, None),
+        other => return Err(format!("unsupported cast type '{}'", other)),
+    };
+
+    Ok((column.to_string(), data_type))
+}
+
+fn exit_on_error(result: Result<(), Box<dyn std::error::Error>>) {
+    if let Err(e) = result {
+        eprintln!("ERROR: {}", e);
+        std::process::exit(1);
+    }
 }
 
 fn main() {
     let cli = Cli::parse();
 
     match &cli.command {
-        Some(Commands::Peak { file, batch_size }) => peak::peak(file, *batch_size).unwrap(),
-        Some(Commands::Edit { file, batch_size }) => sql_editor::edit(file, *batch_size).unwrap(),
+        Some(Commands::Peak {
+            file,
+            batch_size,
+            max_columns,
+            columns,
+            async_reader,
+            fast,
+            cast,
+            rg_filter,
+            fill_width,
+            debug_io,
+            select_row,
+            dict_keys,
+            max_nested_chars,
+            null_text,
+            freeze_first,
+            transpose,
+        }) => exit_on_error(peak::peak(
+            &resolve_file(file),
+            PeakOptions {
+                batch_size: *batch_size,
+                max_columns: *max_columns,
+                columns: columns.clone(),
+                async_reader: *async_reader,
+                fast: *fast,
+                casts: cast.clone(),
+                rg_filter: rg_filter.clone(),
+                fill_width: *fill_width,
+                debug_io: *debug_io,
+                select_row: *select_row,
+                dict_keys: *dict_keys,
+                max_nested_chars: *max_nested_chars,
+                null_text: null_text.clone(),
+                freeze_first: *freeze_first,
+                transpose: *transpose,
+            },
+        )),
+        Some(Commands::Edit {
+            file,
+            batch_size,
+            sql_file,
+            row_group_size,
+            preview_rows,
+            no_auto_limit,
+        }) => exit_on_error(sql_editor::edit(
+            &resolve_file(file),
+            *batch_size,
+            sql_file.as_ref(),
+            *row_group_size,
+            *preview_rows,
+            !*no_auto_limit,
+        )),
+        Some(Commands::Query {
+            file,
+            sql,
+            format,
+            table_style,
+            fail_on_empty,
+        }) => exit_on_error(query::query(
+            file,
+            sql,
+            *format,
+            *table_style,
+            *fail_on_empty,
+        )),
+        Some(Commands::Schema { file }) => exit_on_error(inspect::schema(file)),
+        Some(Commands::Meta { file }) => exit_on_error(inspect::meta(file)),
+        Some(Commands::Validate { file, check_sort }) => {
+            exit_on_error(validate::validate(file, *check_sort, cli.quiet))
+        }
+        Some(Commands::Footer { file }) => exit_on_error(inspect::footer(file)),
+        Some(Commands::Stats { file }) => exit_on_error(inspect::stats(file)),
+        Some(Commands::Head {
+            file,
+            rows,
+            table_style,
+        }) => exit_on_error(dump::head(file, *rows, *table_style)),
+        Some(Commands::Tail {
+            file,
+            rows,
+            table_style,
+        }) => exit_on_error(dump::tail(file, *rows, *table_style)),
+        Some(Commands::ToCsv {
+            input,
+            output,
+            delimiter,
+            no_header,
+        }) => exit_on_error(dump::to_csv(
+            input,
+            output,
+            *delimiter,
+            !*no_header,
+            cli.quiet,
+        )),
+        Some(Commands::Merge { output, inputs }) => {
+            exit_on_error(merge::merge(output, inputs, cli.quiet))
+        }
         None => todo!(),
     }
 }
@@ -0,0 +1,97 @@
+use std::{fs::File, path::PathBuf, process::exit};
+
+use arrow::datatypes::Schema;
+use parquet::arrow::{ArrowWriter, arrow_reader::ParquetRecordBatchReaderBuilder};
+
+use crate::{errors::PeakError, utils::validate_extension};
+
+fn schema_mismatch(expected: &Schema, actual: &Schema) -> Option<String> {
+    if expected.fields().len() != actual.fields().len() {
+        return Some(format!(
+            "expected {} columns, found {}",
+            expected.fields().len(),
+            actual.fields().len()
+        ));
+    }
+
+    expected
+        .fields()
+        .iter()
+        .zip(actual.fields().iter())
+        .find(|(e, a)| {
+            e.name() != a.name()
+                || e.data_type() != a.data_type()
+                || e.is_nullable() != a.is_nullable()
+        })
+        .map(|(e, a)| {
+            format!(
+                "column '{}' ({}) does not match '{}' ({})",
+                e.name(),
+                e.data_type(),
+                a.name(),
+                a.data_type()
+            )
+        })
+}
+
+pub fn merge(
+    output: &PathBuf,
+    inputs: &[PathBuf],
+    quiet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !inputs
+        .iter()
+        .chain(std::iter::once(output))
+        .all(validate_extension)
+    {
+        eprintln!("ERROR: {}", PeakError::ParquetOnly);
+        exit(1)
+    }
+
+    if inputs.contains(output) {
+        return Err(format!("output {} is also an input", output.display()).into());
+    }
+
+    let mut builders = Vec::with_capacity(inputs.len());
+    for path in inputs {
+        let file = File::open(path)?;
+        builders.push(ParquetRecordBatchReaderBuilder::try_new(file)?);
+    }
+
+    let schema = builders[0].schema().clone();
+    for (path, builder) in inputs.iter().zip(&builders).skip(1) {
+        if let Some(reason) = schema_mismatch(&schema, builder.schema()) {
+            return Err(format!(
+                "schema of {} is incompatible with {}: {}",
+                path.display(),
+                inputs[0].display(),
+                reason
+            )
+            .into());
+        }
+    }
+
+    let file = File::create(output)?;
+    let mut writer = ArrowWriter::try_new(file, schema, None)?;
+    let mut total_rows = 0;
+
+    for builder in builders {
+        for batch in builder.build()? {
+            let batch = batch?;
+            total_rows += batch.num_rows();
+            writer.write(&batch)?;
+        }
+    }
+    writer.close()?;
+
+    if !quiet {
+        eprintln!(
+            "Merged {} rows from {} files into {}",
+            total_rows,
+            inputs.len(),
+            output.display()
+        );
+    }
+
+    Ok(())
+}
@@ -1,21 +1,196 @@
-use std::process::exit;
+use std::{process::exit, sync::Arc};
 
-use arrow::{array::RecordBatch, util::display::array_value_to_string};
+use arrow::{
+    array::{Array, ArrayRef, AsArray, LargeStringArray, RecordBatch, StringArray},
+    datatypes::{DataType, FieldRef, Schema},
+    error::ArrowError,
+    util::display::array_value_to_string,
+};
 
-use crate::{errors::PeakError, table::build_table, utils::validate_extension};
+use crate::{
+    errors::PeakError, prune::RowGroupFilter, source::TextFormat, table::build_table,
+    utils::validate_extension,
+};
 
-pub fn peak(path: &std::path::PathBuf, batch_size: usize) -> Result<(), Box<dyn std::error::Error>> {
-    let valid = validate_extension(path);
+#[derive(Clone)]
+pub struct PeakOptions {
+    pub batch_size: usize,
+    pub max_columns: Option<usize>,
+    pub columns: Vec<String>,
+    pub async_reader: bool,
+    pub fast: bool,
+    pub casts: Vec<(String, DataType)>,
+    pub rg_filter: Option<RowGroupFilter>,
+    pub fill_width: bool,
+    pub debug_io: bool,
+    pub select_row: Option<usize>,
+    pub dict_keys: bool,
+    pub max_nested_chars: Option<usize>,
+    pub null_text: Option<String>,
+    pub freeze_first: bool,
+    pub transpose: bool,
+}
+
+pub fn peak(
+    path: &std::path::PathBuf,
+    options: PeakOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let valid = validate_extension(path) || TextFormat::from_path(path).is_some();
     if !valid {
         eprintln!("ERROR: {}", PeakError::UnsupportedFileType);
         exit(0)
     }
     
-    build_table(path.clone(), batch_size)?;
+    build_table(path.clone(), options)?;
 
     Ok(())
 }
 
+pub const NULL_SENTINEL: &str = "NULL";
+const DECODE_ERROR: &str = "<decode error>";
+const LOSSY_MARKER: &str = " [lossy UTF-8]";
+
+/// The schema with its top-level string columns read as raw bytes. The
+/// Parquet reader rejects a whole batch over one invalid UTF-8 value, so
+/// such files are read this way and decoded with `lossy_strings`.
+pub fn binary_strings(schema: &Schema) -> Schema {
+    let fields: Vec<FieldRef> = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let data_type = match field.data_type() {
+                DataType::Utf8 => DataType::Binary,
+                DataType::LargeUtf8 => DataType::LargeBinary,
+                _ => return field.clone(),
+            };
+            Arc::new(field.as_ref().clone().with_data_type(data_type))
+        })
+        .collect();
+    Schema::new_with_metadata(fields, schema.metadata().clone())
+}
+
+/// Turns columns read through `binary_strings` back into the string types
+/// of `schema`. Invalid bytes become replacement characters and the value
+/// is marked, rather than hidden behind NULL.
+pub fn lossy_strings(batch: &RecordBatch, schema: &Schema) -> Result<RecordBatch, ArrowError> {
+    let lossy = |bytes: &[u8]| match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => format!("{}{}", String::from_utf8_lossy(bytes), LOSSY_MARKER),
+    };
+
+    let mut fields: Vec<FieldRef> = Vec::new();
+    let mut columns: Vec<ArrayRef> = Vec::new();
+    for (field, col) in batch.schema().fields().iter().zip(batch.columns()) {
+        let original = schema.field_with_name(field.name()).ok().map(|f| f.data_type());
+        let column: ArrayRef = match (original, col.data_type()) {
+            (Some(DataType::Utf8), DataType::Binary) => Arc::new(
+                col.as_binary::<i32>()
+                    .iter()
+                    .map(|v| v.map(lossy))
+                    .collect::<StringArray>(),
+            ),
+            (Some(DataType::LargeUtf8), DataType::LargeBinary) => Arc::new(
+                col.as_binary::<i64>()
+                    .iter()
+                    .map(|v| v.map(lossy))
+                    .collect::<LargeStringArray>(),
+            ),
+            _ => {
+                fields.push(field.clone());
+                columns.push(col.clone());
+                continue;
+            }
+        };
+        let data_type = column.data_type().clone();
+        fields.push(Arc::new(field.as_ref().clone().with_data_type(data_type)));
+        columns.push(column);
+    }
+
+    let schema = Schema::new_with_metadata(fields, batch.schema().metadata().clone());
+    RecordBatch::try_new(Arc::new(schema), columns)
+}
+
+/// Renders lists as `[a, b]`, structs as `{field: value}` and maps as
+/// `{key: value}`, recursing into nested values. Nested nulls show as NULL
+/// rather than the formatter's empty string. Scalars use the display
+/// formatter unchanged.
+fn nested_value(col: &ArrayRef, i: usize) -> Result<String, ArrowError> {
+    if col.is_null(i) {
+        return Ok(NULL_SENTINEL.to_string());
+    }
+
+    match col.data_type() {
+        DataType::List(_) => list_items(&col.as_list::<i32>().value(i)),
+        DataType::LargeList(_) => list_items(&col.as_list::<i64>().value(i)),
+        DataType::FixedSizeList(..) => list_items(&col.as_fixed_size_list().value(i)),
+        DataType::Struct(fields) => {
+            let parts = fields
+                .iter()
+                .zip(col.as_struct().columns())
+                .map(|(field, child)| Ok(format!("{}: {}", field.name(), nested_value(child, i)?)))
+                .collect::<Result<Vec<_>, ArrowError>>()?;
+            Ok(format!("{{{}}}", parts.join(", ")))
+        }
+        DataType::Map(..) => {
+            let entries = col.as_map().value(i);
+            let (keys, values) = (entries.column(0), entries.column(1));
+            let parts = (0..entries.len())
+                .map(|j| Ok(format!("{}: {}", nested_value(keys, j)?, nested_value(values, j)?)))
+                .collect::<Result<Vec<_>, ArrowError>>()?;
+            Ok(format!("{{{}}}", parts.join(", ")))
+        }
+        _ => array_value_to_string(col, i),
+    }
+}
+
+fn list_items(values: &ArrayRef) -> Result<String, ArrowError> {
+    let items = (0..values.len())
+        .map(|j| nested_value(values, j))
+        .collect::<Result<Vec<_>, ArrowError>>()?;
+    Ok(format!("[{}]", items.join(", ")))
+}
+
+/// Shortens list, struct and map cells to `max` characters, ending in `…`.
+pub fn truncate_nested(batch: &RecordBatch, rows: &mut [Vec<String>], max: usize) {
+    for (col_idx, field) in batch.schema().fields().iter().enumerate() {
+        if !field.data_type().is_nested() {
+            continue;
+        }
+        for row in rows.iter_mut() {
+            let cell = &mut row[col_idx];
+            if cell.chars().count() > max {
+                *cell = cell
+                    .chars()
+                    .take(max.saturating_sub(1))
+                    .chain(std::iter::once('…'))
+                    .collect();
+            }
+        }
+    }
+}
+
+/// Prefixes the cells of dictionary-encoded columns with their dictionary
+/// index, e.g. `3:Active`, to show how categorical data is encoded.
+pub fn prefix_dictionary_keys(batch: &RecordBatch, rows: &mut [Vec<String>]) {
+    for (col_idx, col) in batch.columns().iter().enumerate() {
+        let Some(dictionary) = col.as_any_dictionary_opt() else {
+            continue;
+        };
+        let keys = dictionary.normalized_keys();
+        for (i, row) in rows.iter_mut().enumerate() {
+            if !col.is_null(i) {
+                row[col_idx] = format!("{}:{}", keys[i], row[col_idx]);
+            }
+        }
+    }
+}
+
+/// Display text of one non-null value. Values the formatter rejects are
+/// marked as errors rather than shown as NULL.
+pub fn cell_text(col: &ArrayRef, i: usize) -> String {
+    nested_value(col, i).unwrap_or_else(|_| DECODE_ERROR.to_string())
+}
+
 pub fn batch_to_rows(batch: &RecordBatch) -> Vec<Vec<String>> {
     let batch_length = batch.num_rows();
     let mut rows: Vec<Vec<String>> = Vec::new();
@@ -24,7 +199,12 @@ pub fn batch_to_rows(batch: &RecordBatch) -> Vec<Vec<String>> {
         let row_strings: Vec<String> = batch
             .columns()
             .iter()
-            .map(|col| array_value_to_string(col, i).unwrap_or_else(|_| "NULL".to_string()))
+            .map(|col| {
+                if col.is_null(i) {
+                    return NULL_SENTINEL.to_string();
+                }
+                cell_text(col, i)
+            })
             .collect();
         rows.push(row_strings);
     }
@@ -0,0 +1,176 @@
+use std::fmt;
+
+use arrow::{
+    array::{Array, BooleanArray, Scalar, StringArray},
+    compute::{
+        cast,
+        kernels::cmp::{eq, gt, gt_eq, lt, lt_eq},
+    },
+    datatypes::Schema,
+};
+use parquet::{
+    arrow::arrow_reader::statistics::StatisticsConverter, file::metadata::ParquetMetaData,
+};
+
+/// A comparison operator, shared by `--rg-filter` and the viewer's
+/// mask conditions.
+#[derive(Clone, Copy)]
+pub enum CmpOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+impl CmpOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            CmpOp::Eq => "=",
+            CmpOp::NotEq => "!=",
+            CmpOp::Lt => "<",
+            CmpOp::LtEq => "<=",
+            CmpOp::Gt => ">",
+            CmpOp::GtEq => ">=",
+        }
+    }
+
+    /// Splits the operator off the front of `rest`, longest symbol first.
+    /// `==` is accepted as a spelling of `=`.
+    pub fn strip_prefix(rest: &str) -> Option<(CmpOp, &str)> {
+        [
+            (">=", CmpOp::GtEq),
+            ("<=", CmpOp::LtEq),
+            ("!=", CmpOp::NotEq),
+            ("==", CmpOp::Eq),
+            ("=", CmpOp::Eq),
+            (">", CmpOp::Gt),
+            ("<", CmpOp::Lt),
+        ]
+        .into_iter()
+        .find_map(|(symbol, op)| rest.strip_prefix(symbol).map(|value| (op, value)))
+    }
+}
+
+/// A single `column OP value` comparison checked against row group min/max
+/// statistics, e.g. `date>=2024-01-01`.
+#[derive(Clone)]
+pub struct RowGroupFilter {
+    column: String,
+    op: CmpOp,
+    value: String,
+}
+
+impl fmt::Display for RowGroupFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}{}", self.column, self.op.symbol(), self.value)
+    }
+}
+
+pub fn parse_rg_filter(spec: &str) -> Result<RowGroupFilter, String> {
+    let at = spec
+        .find(['<', '>', '=', '!'])
+        .ok_or("expected COLUMN OP VALUE with one of = != < <= > >=")?;
+    let (column, rest) = spec.split_at(at);
+
+    let (op, value) =
+        CmpOp::strip_prefix(rest).ok_or_else(|| format!("unknown operator in '{}'", spec))?;
+
+    let column = column.trim();
+    let value = value.trim().trim_matches(|c| c == '\'' || c == '"');
+    if column.is_empty() || value.is_empty() {
+        return Err("expected COLUMN OP VALUE, e.g. date>=2024-01-01".to_string());
+    }
+
+    Ok(RowGroupFilter {
+        column: column.to_string(),
+        op,
+        value: value.to_string(),
+    })
+}
+
+/// True unless the comparison is known to be false. Missing statistics
+/// come through as nulls, and such row groups have to be kept.
+fn maybe(result: &BooleanArray, i: usize) -> bool {
+    result.is_null(i) || result.value(i)
+}
+
+fn definitely(result: &BooleanArray, i: usize) -> bool {
+    result.is_valid(i) && result.value(i)
+}
+
+impl RowGroupFilter {
+    /// Indices of the row groups whose min/max range could hold a matching
+    /// row. Only footer metadata is read.
+    pub fn select_row_groups(
+        &self,
+        arrow_schema: &Schema,
+        metadata: &ParquetMetaData,
+    ) -> Result<Vec<usize>, Box<dyn std::error::Error>> {
+        let converter = StatisticsConverter::try_new(
+            &self.column,
+            arrow_schema,
+            metadata.file_metadata().schema_descr(),
+        )?;
+        let mins = converter.row_group_mins(metadata.row_groups())?;
+        let maxes = converter.row_group_maxes(metadata.row_groups())?;
+
+        let literal = cast(
+            &StringArray::from(vec![self.value.as_str()]),
+            mins.data_type(),
+        )?;
+        if literal.is_null(0) {
+            return Err(format!(
+                "cannot compare '{}' with column '{}' of type {}",
+                self.value,
+                self.column,
+                mins.data_type()
+            )
+            .into());
+        }
+        let value = Scalar::new(literal);
+
+        let keep: Vec<bool> = match self.op {
+            CmpOp::Gt => {
+                let above = gt(&maxes, &value)?;
+                (0..maxes.len()).map(|i| maybe(&above, i)).collect()
+            }
+            CmpOp::GtEq => {
+                let above = gt_eq(&maxes, &value)?;
+                (0..maxes.len()).map(|i| maybe(&above, i)).collect()
+            }
+            CmpOp::Lt => {
+                let below = lt(&mins, &value)?;
+                (0..mins.len()).map(|i| maybe(&below, i)).collect()
+            }
+            CmpOp::LtEq => {
+                let below = lt_eq(&mins, &value)?;
+                (0..mins.len()).map(|i| maybe(&below, i)).collect()
+            }
+            CmpOp::Eq => {
+                let low = lt_eq(&mins, &value)?;
+                let high = gt_eq(&maxes, &value)?;
+                (0..mins.len())
+                    .map(|i| maybe(&low, i) && maybe(&high, i))
+                    .collect()
+            }
+            CmpOp::NotEq => {
+                // Only a row group where every value equals the literal
+                // can be skipped.
+                let min_eq = eq(&mins, &value)?;
+                let max_eq = eq(&maxes, &value)?;
+                (0..mins.len())
+                    .map(|i| !(definitely(&min_eq, i) && definitely(&max_eq, i)))
+                    .collect()
+            }
+        };
+
+        Ok(keep
+            .iter()
+            .enumerate()
+            .filter(|(_, keep)| **keep)
+            .map(|(idx, _)| idx)
+            .collect())
+    }
+}
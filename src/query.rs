@@ -0,0 +1,124 @@
+use std::{
+    io::{StdoutLock, Write},
+    path::PathBuf,
+    process::exit,
+};
+
+use arrow::{array::RecordBatch, csv, datatypes::SchemaRef, json};
+use clap::ValueEnum;
+use futures::StreamExt;
+
+use crate::{
+    dump::{TableStyle, format_table},
+    errors::PeakError,
+    peak::batch_to_rows,
+    sql_editor::session_context,
+    utils::{nested_as_text, validate_extension},
+};
+
+const QUERY_BATCH_SIZE: usize = 8192;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum QueryFormat {
+    /// Aligned text table
+    Table,
+    /// CSV with a header row
+    Csv,
+    /// A JSON array of row objects
+    Json,
+}
+
+/// Writes result batches to stdout as they arrive, so CSV and JSON exports
+/// never hold the whole result. The table format has to see every row to
+/// align its columns, so it keeps the formatted rows until the end.
+enum ResultWriter {
+    Table {
+        style: TableStyle,
+        header: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    Csv(csv::Writer<StdoutLock<'static>>),
+    Json(json::ArrayWriter<StdoutLock<'static>>),
+}
+
+impl ResultWriter {
+    fn new(format: QueryFormat, style: TableStyle, schema: &SchemaRef) -> Self {
+        let out = std::io::stdout().lock();
+        match format {
+            QueryFormat::Table => ResultWriter::Table {
+                style,
+                header: schema
+                    .fields()
+                    .iter()
+                    .map(|f| f.name().to_owned())
+                    .collect(),
+                rows: Vec::new(),
+            },
+            QueryFormat::Csv => ResultWriter::Csv(csv::Writer::new(out)),
+            QueryFormat::Json => ResultWriter::Json(json::ArrayWriter::new(out)),
+        }
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            ResultWriter::Table { rows, .. } => rows.extend(batch_to_rows(batch)),
+            ResultWriter::Csv(writer) => writer.write(&nested_as_text(batch)?)?,
+            ResultWriter::Json(writer) => writer.write(batch)?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            ResultWriter::Table {
+                style,
+                header,
+                rows,
+            } => print!("{}", format_table(style, &header, &rows)),
+            ResultWriter::Csv(_) => {}
+            ResultWriter::Json(mut writer) => {
+                writer.finish()?;
+                writeln!(writer.into_inner())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs one SQL statement against the file (registered as `data`) and
+/// prints the result without starting the TUI. With `fail_on_empty`, a
+/// result with no rows is an error so scripts can assert rows exist.
+pub fn query(
+    path: &PathBuf,
+    sql: &str,
+    format: QueryFormat,
+    style: TableStyle,
+    fail_on_empty: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !validate_extension(path) {
+        eprintln!("ERROR: {}", PeakError::ParquetOnly);
+        exit(1)
+    }
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    let rows = runtime.block_on(async {
+        let ctx = session_context(path, QUERY_BATCH_SIZE).await?;
+        let mut stream = ctx.sql(sql).await?.execute_stream().await?;
+
+        let mut writer = ResultWriter::new(format, style, &stream.schema());
+        let mut rows = 0;
+        while let Some(batch) = stream.next().await {
+            let batch = batch?;
+            rows += batch.num_rows();
+            writer.write(&batch)?;
+        }
+        writer.finish()?;
+        Ok::<_, Box<dyn std::error::Error>>(rows)
+    })?;
+
+    if fail_on_empty && rows == 0 {
+        return Err("query returned no rows (--fail-on-empty)".into());
+    }
+
+    Ok(())
+}
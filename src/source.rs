@@ -0,0 +1,112 @@
+use std::{fs::File, io::BufReader, path::PathBuf, sync::Arc};
+
+use arrow::{array::RecordBatch, csv, datatypes::SchemaRef, error::ArrowError, json};
+
+const INFER_ROWS: usize = 1000;
+const COUNT_BATCH_SIZE: usize = 8192;
+
+type Batches = Box<dyn Iterator<Item = Result<RecordBatch, ArrowError>>>;
+
+/// Row-oriented formats read alongside Parquet. They have no footer, so
+/// schemas are inferred, row counts take a full pass, and every read starts
+/// at the top of the file.
+#[derive(Clone, Copy, PartialEq)]
+pub enum TextFormat {
+    Csv,
+    Json,
+}
+
+impl TextFormat {
+    /// Picks the format from the extension; `None` for anything else.
+    pub fn from_path(path: &PathBuf) -> Option<TextFormat> {
+        let ext = path.extension()?.to_ascii_lowercase();
+        if ext == "csv" {
+            Some(TextFormat::Csv)
+        } else if ext == "json" || ext == "ndjson" || ext == "jsonl" {
+            Some(TextFormat::Json)
+        } else {
+            None
+        }
+    }
+}
+
+/// Infers column types from the first rows. CSV files must have a header;
+/// JSON files hold one object per line.
+pub fn infer_schema(
+    path: &PathBuf,
+    format: TextFormat,
+) -> Result<SchemaRef, Box<dyn std::error::Error>> {
+    let file = File::open(path)?;
+    let (schema, _) = match format {
+        TextFormat::Csv => csv::reader::Format::default()
+            .with_header(true)
+            .infer_schema(file, Some(INFER_ROWS))?,
+        TextFormat::Json => {
+            json::reader::infer_json_schema(BufReader::new(file), Some(INFER_ROWS))?
+        }
+    };
+    Ok(Arc::new(schema))
+}
+
+/// Batches from the top of the file, keeping only the given top-level
+/// columns when set.
+fn batches(
+    path: &PathBuf,
+    format: TextFormat,
+    schema: &SchemaRef,
+    batch_size: usize,
+    columns: Option<&[usize]>,
+) -> Result<Batches, Box<dyn std::error::Error>> {
+    let file = File::open(path)?;
+
+    let reader: Batches = match format {
+        TextFormat::Csv => {
+            let mut builder = csv::ReaderBuilder::new(schema.clone())
+                .with_header(true)
+                .with_batch_size(batch_size);
+            if let Some(columns) = columns {
+                builder = builder.with_projection(columns.to_vec());
+            }
+            Box::new(builder.build(file)?)
+        }
+        TextFormat::Json => {
+            // Fields missing from the schema are skipped while decoding.
+            let schema = match columns {
+                Some(columns) => Arc::new(schema.project(columns)?),
+                None => schema.clone(),
+            };
+            Box::new(
+                json::ReaderBuilder::new(schema)
+                    .with_batch_size(batch_size)
+                    .build(BufReader::new(file))?,
+            )
+        }
+    };
+    Ok(reader)
+}
+
+pub fn count_rows(
+    path: &PathBuf,
+    format: TextFormat,
+    schema: &SchemaRef,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut rows = 0;
+    for batch in batches(path, format, schema, COUNT_BATCH_SIZE, None)? {
+        rows += batch?.num_rows();
+    }
+    Ok(rows)
+}
+
+pub fn read_batch(
+    path: &PathBuf,
+    format: TextFormat,
+    schema: &SchemaRef,
+    batch_size: usize,
+    columns: Option<&[usize]>,
+    batch_idx: usize,
+) -> Result<Option<RecordBatch>, Box<dyn std::error::Error>> {
+    let batch = batches(path, format, schema, batch_size, columns)?
+        .nth(batch_idx)
+        .transpose()?;
+    Ok(batch)
+}
@@ -1,8 +1,19 @@
-use std::{fs::File, path::PathBuf};
+use std::{
+    fs::File,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
-use arrow::array::RecordBatch;
+use arrow::{
+    array::{AsArray, RecordBatch},
+    datatypes::{Int64Type, Schema, SchemaRef},
+    util::display::array_value_to_string,
+};
 use datafusion::prelude::*;
-use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::{
+    arrow::arrow_reader::ParquetRecordBatchReaderBuilder,
+    basic::{Compression, GzipLevel, ZstdLevel},
+};
 use ratatui::{
     DefaultTerminal, Frame,
     crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
@@ -12,10 +23,20 @@ use ratatui::{
 };
 use tui_textarea::TextArea;
 
-use crate::{errors::PeakError, peak::batch_to_rows, utils::validate_extension};
+use crate::{
+    errors::PeakError,
+    peak::batch_to_rows,
+    source::{self, TextFormat},
+    utils::{
+        clock_time, content_widths, describe_fields, format_count, header_cell, int96_notice,
+        nested_as_text, record_lines, validate_extension,
+    },
+};
 
 const VISIBLE_COLS: usize = 10;
-const MAX_PREVIEW_ROWS: usize = 1000;
+pub const MAX_PREVIEW_ROWS: usize = 1000;
+const PREVIEW_LIMIT_STEP: usize = 500;
+const MAX_AUTO_WIDTH: usize = 40;
 const DEFAULT_SQL: &str = "SELECT * FROM data LIMIT 100";
 
 enum FocusedPane {
@@ -24,6 +45,15 @@ enum FocusedPane {
     SaveDialog,
 }
 
+/// Snapshot of the unfiltered data taken when a query first replaces it, so
+/// the split view can show both side by side.
+struct OriginalView {
+    header: Vec<String>,
+    column_types: Vec<String>,
+    rows: Vec<Vec<String>>,
+    batch_idx: usize,
+}
+
 enum ExecutionState {
     Idle,
     Executing,
@@ -34,122 +64,273 @@ enum ExecutionState {
 struct App<'a> {
     file_path: PathBuf,
     batch_size: usize,
+    initial_sql: String,
 
     sql_textarea: TextArea<'a>,
     save_dialog: TextArea<'a>,
     focused_pane: FocusedPane,
     execution_state: ExecutionState,
     show_save_dialog: bool,
+    save_compression: Compression,
+    row_group_size: Option<usize>,
+    preview_limit: usize,
+    auto_limit: bool,
+    ctx: Option<SessionContext>,
+    show_schema_popup: bool,
+    schema_scroll: u16,
+    show_detail: bool,
+    detail_scroll: u16,
+    show_cell: bool,
+    cell_scroll: u16,
+    analyzed_plan: Option<String>,
+    plan_scroll: u16,
+    activity: Vec<String>,
+    show_activity: bool,
+    activity_scroll: u16,
 
     table_state: TableState,
     current_batch_idx: usize,
     current_rows: Vec<Vec<String>>,
     header: Vec<String>,
+    column_types: Vec<String>,
     col_offset: usize,
+    cursor_col: usize,
+    show_ordinals: bool,
     total_batches: usize,
     total_rows: usize,
+    original_schema: SchemaRef,
 
     is_filtered: bool,
+    result_batches: Vec<RecordBatch>,
+    original_view: Option<OriginalView>,
+    show_split: bool,
+    result_schema: Option<SchemaRef>,
+    result_query: Option<String>,
+    matched_rows: Option<usize>,
+    executed_sql: Option<String>,
+    query_time: Option<Duration>,
+    notice: Option<String>,
 }
 
 impl<'a> App<'a> {
-    fn new(file_path: PathBuf, batch_size: usize) -> Result<Self, Box<dyn std::error::Error>> {
-        let file = File::open(&file_path)?;
-        let builder = ParquetRecordBatchReaderBuilder::try_new(file)?.with_batch_size(batch_size);
-
-        let metadata = builder.metadata();
-        let total_rows = metadata.file_metadata().num_rows() as usize;
+    fn new(
+        file_path: PathBuf,
+        batch_size: usize,
+        initial_sql: String,
+        row_group_size: Option<usize>,
+        preview_rows: usize,
+        auto_limit: bool,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let (arrow_schema, total_rows, first_batch, notice) =
+            open_original(&file_path, batch_size)?;
         let total_batches = (total_rows + batch_size - 1) / batch_size;
 
-        let arrow_schema = builder.schema();
         let header: Vec<String> = arrow_schema
             .fields()
             .iter()
             .map(|f| f.name().to_owned())
             .collect();
-
-        let mut reader = builder.build()?;
-        let first_batch = reader.next().ok_or("No data in file")??;
+        let column_types = column_types(&arrow_schema);
         let current_rows = batch_to_rows(&first_batch);
 
-        let mut sql_textarea = TextArea::default();
-        sql_textarea.set_block(Block::default().borders(Borders::ALL).title("SQL Editor"));
-        sql_textarea.insert_str(DEFAULT_SQL);
+        let sql_textarea = new_sql_textarea(&initial_sql);
 
         let mut save_dialog = TextArea::default();
-        save_dialog.set_block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Save As (Enter to save, Esc to cancel)")
-                .border_style(Style::default().fg(Color::Green)),
-        );
         save_dialog.insert_str("output.parquet");
 
         Ok(Self {
             file_path,
             batch_size,
+            initial_sql,
             sql_textarea,
             save_dialog,
             focused_pane: FocusedPane::SqlEditor,
             execution_state: ExecutionState::Idle,
             show_save_dialog: false,
+            save_compression: Compression::UNCOMPRESSED,
+            row_group_size,
+            preview_limit: preview_rows,
+            auto_limit,
+            ctx: None,
+            show_schema_popup: false,
+            schema_scroll: 0,
+            show_detail: false,
+            detail_scroll: 0,
+            show_cell: false,
+            cell_scroll: 0,
+            analyzed_plan: None,
+            plan_scroll: 0,
+            activity: Vec::new(),
+            show_activity: false,
+            activity_scroll: 0,
             table_state: TableState::default().with_selected(0),
             current_batch_idx: 0,
             current_rows,
             header,
+            column_types,
             col_offset: 0,
+            cursor_col: 0,
+            show_ordinals: false,
             total_batches,
             total_rows,
+            original_schema: arrow_schema,
             is_filtered: false,
+            result_batches: Vec::new(),
+            original_view: None,
+            show_split: false,
+            result_schema: None,
+            result_query: None,
+            matched_rows: None,
+            executed_sql: None,
+            query_time: None,
+            notice,
         })
     }
 
-    async fn execute_sql(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let sql = self.sql_textarea.lines().join(" ").trim().to_string();
+    /// Registers the file on first use and hands out the same context after
+    /// that, so repeated queries don't re-read the footer.
+    async fn session_context(&mut self) -> Result<SessionContext, Box<dyn std::error::Error>> {
+        if let Some(ctx) = &self.ctx {
+            return Ok(ctx.clone());
+        }
+
+        let ctx = session_context(&self.file_path, self.batch_size).await?;
+        self.ctx = Some(ctx.clone());
+        Ok(ctx)
+    }
+
+    /// Records an action in the session's activity log, one line per entry.
+    fn log(&mut self, action: String) {
+        self.activity
+            .push(format!("{}  {}", clock_time(), action.replace('\n', " ")));
+    }
+
+    /// Runs `count(*)` over the last query without the preview LIMIT. This is
+    /// a second scan, so it only happens on request.
+    async fn count_result_rows(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(query) = self.result_query.clone() else {
+            self.execution_state =
+                ExecutionState::Error("Execute a query first before counting".to_string());
+            return Ok(());
+        };
+
+        let ctx = self.session_context().await?;
+        let count_sql = format!(
+            "SELECT count(*) FROM ({}\n) AS counted",
+            query.trim_end_matches(';')
+        );
+        let batches = ctx.sql(&count_sql).await?.collect().await?;
+
+        let count = batches
+            .first()
+            .filter(|b| b.num_rows() > 0)
+            .map(|b| b.column(0).as_primitive::<Int64Type>().value(0))
+            .ok_or("count(*) returned no rows")?;
+
+        self.matched_rows = Some(count as usize);
+        self.log(format!("Counted {} rows: {}", count, query));
+        self.execution_state = ExecutionState::Success;
+
+        Ok(())
+    }
+
+    /// Runs `EXPLAIN ANALYZE` on the last query (without the preview LIMIT)
+    /// and keeps the plan annotated with per-operator rows and timings.
+    async fn analyze_query(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(query) = self.result_query.clone() else {
+            self.execution_state =
+                ExecutionState::Error("Execute a query first before analyzing".to_string());
+            return Ok(());
+        };
+
+        let ctx = self.session_context().await?;
+        let explain_sql = format!("EXPLAIN ANALYZE {}", query.trim_end_matches(';'));
+        let batches = ctx.sql(&explain_sql).await?.collect().await?;
+
+        let mut plan = Vec::new();
+        for batch in &batches {
+            let column = batch
+                .column_by_name("plan")
+                .ok_or("EXPLAIN returned no plan")?;
+            for i in 0..batch.num_rows() {
+                plan.push(array_value_to_string(column, i)?);
+            }
+        }
+
+        self.analyzed_plan = Some(plan.join("\n"));
+        self.log(format!("Analyzed: {}", query));
+        self.plan_scroll = 0;
+        self.execution_state = ExecutionState::Success;
+
+        Ok(())
+    }
+
+    async fn execute_sql(&mut self, sql: String) -> Result<(), Box<dyn std::error::Error>> {
+        // A trailing `;` would end the statement before the added LIMIT.
+        let sql = sql.trim().trim_end_matches(';').trim_end().to_string();
 
         if sql.is_empty() {
             self.execution_state = ExecutionState::Error("SQL query is empty".to_string());
             return Ok(());
         }
 
-        let config = SessionConfig::new()
-            .with_target_partitions(1)
-            .with_batch_size(self.batch_size);
-        let ctx = SessionContext::new_with_config(config);
-
-        ctx.register_parquet(
-            "data",
-            self.file_path.to_str().unwrap(),
-            ParquetReadOptions::default(),
-        )
-        .await?;
+        let ctx = match self.session_context().await {
+            Ok(ctx) => ctx,
+            Err(e) => {
+                self.log(format!("Failed to open the file ({}): {}", e, sql));
+                return Err(e);
+            }
+        };
 
-        let sql_with_limit = if !sql.to_uppercase().contains("LIMIT")
+        let sql_with_limit = if self.auto_limit
+            && !sql.to_uppercase().contains("LIMIT")
             && sql.to_uppercase().trim_start().starts_with("SELECT")
         {
-            format!("{} LIMIT {}", sql, MAX_PREVIEW_ROWS)
+            // On its own line so a trailing `-- comment` can't swallow it.
+            format!("{}\nLIMIT {}", sql, self.preview_limit)
         } else {
             sql.clone()
         };
+        self.executed_sql = Some(sql_with_limit.clone());
+        self.result_query = Some(sql.clone());
+        self.matched_rows = None;
+        self.query_time = None;
+
+        let started = Instant::now();
+        let result = match ctx.sql(&sql_with_limit).await {
+            Ok(df) => df.collect().await.map_err(|e| format!("Execution: {}", e)),
+            Err(e) => Err(format!("SQL: {}", e)),
+        };
+        let elapsed = started.elapsed();
 
-        match ctx.sql(&sql_with_limit).await {
-            Ok(df) => match df.collect().await {
-                Ok(batches) => {
-                    if batches.is_empty() {
-                        self.execution_state =
-                            ExecutionState::Error("Query returned no results".to_string());
-                        self.load_original_data()?;
-                    } else {
-                        self.update_with_results(batches)?;
-                        self.execution_state = ExecutionState::Success;
-                    }
-                }
-                Err(e) => {
-                    self.execution_state = ExecutionState::Error(format!("Execution: {}", e));
+        match result {
+            Ok(batches) if batches.is_empty() => {
+                self.execution_state =
+                    ExecutionState::Error("Query returned no results".to_string());
+                self.log(format!("Ran without results: {}", sql_with_limit));
+                self.load_original_data()?;
+            }
+            Ok(batches) => {
+                let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+                if let Err(e) = self.update_with_results(batches) {
+                    self.log(format!(
+                        "Failed to show results ({}): {}",
+                        e, sql_with_limit
+                    ));
+                    return Err(e);
                 }
-            },
+                self.query_time = Some(elapsed);
+                self.execution_state = ExecutionState::Success;
+                self.log(format!(
+                    "Ran ({} rows, {}ms): {}",
+                    rows,
+                    elapsed.as_millis(),
+                    sql_with_limit
+                ));
+            }
             Err(e) => {
-                self.execution_state = ExecutionState::Error(format!("SQL: {}", e));
+                self.log(format!("Failed ({}): {}", e, sql_with_limit));
+                self.execution_state = ExecutionState::Error(e);
             }
         }
 
@@ -164,82 +345,97 @@ impl<'a> App<'a> {
             return Ok(());
         }
 
-        let new_header: Vec<String> = batches[0]
-            .schema()
+        if !self.is_filtered {
+            self.original_view = Some(OriginalView {
+                header: std::mem::take(&mut self.header),
+                column_types: std::mem::take(&mut self.column_types),
+                rows: std::mem::take(&mut self.current_rows),
+                batch_idx: self.current_batch_idx,
+            });
+        }
+
+        let schema = batches[0].schema();
+        let new_header: Vec<String> = schema
             .fields()
             .iter()
             .map(|f| f.name().to_owned())
             .collect();
 
-        let mut all_rows = Vec::new();
-        for batch in &batches {
-            all_rows.extend(batch_to_rows(batch));
-        }
+        // Batches are kept as Arrow data and only the one on screen is turned
+        // into strings, the same way original data is paged.
+        let batches: Vec<RecordBatch> = batches.into_iter().filter(|b| b.num_rows() > 0).collect();
 
         self.header = new_header;
-        self.current_rows = all_rows;
+        self.column_types = column_types(&schema);
+        self.current_rows = batches.first().map(batch_to_rows).unwrap_or_default();
         self.current_batch_idx = 0;
-        self.total_rows = self.current_rows.len();
-        self.total_batches = 1;
+        self.total_rows = batches.iter().map(|b| b.num_rows()).sum();
+        self.total_batches = batches.len().max(1);
+        self.result_batches = batches;
         self.col_offset = 0;
+        self.cursor_col = 0;
         self.table_state.select(Some(0));
         self.is_filtered = true;
+        self.result_schema = Some(schema);
 
         Ok(())
     }
 
     fn load_original_data(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let file = File::open(&self.file_path)?;
-        let builder =
-            ParquetRecordBatchReaderBuilder::try_new(file)?.with_batch_size(self.batch_size);
-
-        let metadata = builder.metadata();
-        let total_rows = metadata.file_metadata().num_rows() as usize;
+        let (arrow_schema, total_rows, first_batch, _) =
+            open_original(&self.file_path, self.batch_size)?;
         let total_batches = (total_rows + self.batch_size - 1) / self.batch_size;
 
-        let arrow_schema = builder.schema();
         let header: Vec<String> = arrow_schema
             .fields()
             .iter()
             .map(|f| f.name().to_owned())
             .collect();
-
-        let mut reader = builder.build()?;
-        let first_batch = reader.next().ok_or("No data in file")??;
+        let column_types = column_types(&arrow_schema);
         let current_rows = batch_to_rows(&first_batch);
 
+        self.column_types = column_types;
         self.header = header;
         self.current_rows = current_rows;
         self.current_batch_idx = 0;
         self.total_rows = total_rows;
         self.total_batches = total_batches;
+        self.original_schema = arrow_schema;
         self.col_offset = 0;
+        self.cursor_col = 0;
         self.table_state.select(Some(0));
         self.is_filtered = false;
+        self.result_batches.clear();
+        self.original_view = None;
+        self.result_schema = None;
+        self.result_query = None;
+        self.matched_rows = None;
+        self.executed_sql = None;
+        self.query_time = None;
         self.execution_state = ExecutionState::Idle;
 
-        self.sql_textarea = TextArea::default();
-        self.sql_textarea
-            .set_block(Block::default().borders(Borders::ALL).title("SQL Editor"));
-        self.sql_textarea.insert_str(DEFAULT_SQL);
+        self.sql_textarea = new_sql_textarea(&self.initial_sql);
 
         Ok(())
     }
 
     fn load_batch(&mut self, batch_idx: usize) -> Result<(), Box<dyn std::error::Error>> {
         if self.is_filtered {
+            if let Some(batch) = self.result_batches.get(batch_idx) {
+                self.current_rows = batch_to_rows(batch);
+                self.current_batch_idx = batch_idx;
+                self.table_state.select(Some(0));
+            }
             return Ok(());
         }
 
-        let file = File::open(&self.file_path)?;
-        let builder =
-            ParquetRecordBatchReaderBuilder::try_new(file)?.with_batch_size(self.batch_size);
-
-        let reader = builder.build()?;
-        let mut skipped_reader = reader.skip(batch_idx);
-
-        if let Some(batch_result) = skipped_reader.next() {
-            let batch = batch_result?;
+        let batch = read_original_batch(
+            &self.file_path,
+            &self.original_schema,
+            self.batch_size,
+            batch_idx,
+        )?;
+        if let Some(batch) = batch {
             self.current_rows = batch_to_rows(&batch);
             self.current_batch_idx = batch_idx;
             self.table_state.select(Some(0));
@@ -264,6 +460,29 @@ impl<'a> App<'a> {
         }
     }
 
+    fn load_first_batch(&mut self) {
+        if self.current_batch_idx > 0 {
+            if let Err(e) = self.load_batch(0) {
+                self.execution_state = ExecutionState::Error(format!("Error loading batch: {}", e));
+                return;
+            }
+        }
+        self.table_state.select(Some(0));
+    }
+
+    /// Loads the final batch and selects its last row.
+    fn load_last_batch(&mut self) {
+        let last = self.total_batches.saturating_sub(1);
+        if self.current_batch_idx != last {
+            if let Err(e) = self.load_batch(last) {
+                self.execution_state = ExecutionState::Error(format!("Error loading batch: {}", e));
+                return;
+            }
+        }
+        self.table_state
+            .select(self.current_rows.len().checked_sub(1));
+    }
+
     fn toggle_focus(&mut self) {
         if !self.show_save_dialog {
             self.focused_pane = match self.focused_pane {
@@ -275,65 +494,153 @@ impl<'a> App<'a> {
     }
 
     fn save_results(&self, output_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        use arrow::datatypes::Schema;
         use parquet::arrow::ArrowWriter;
         use parquet::file::properties::WriterProperties;
-        use std::sync::Arc;
 
         if !self.is_filtered {
             return Err("No SQL results to save. Execute a query first.".into());
         }
 
-        let schema = Schema::new(
-            self.header
-                .iter()
-                .enumerate()
-                .map(|(_i, name)| {
-                    arrow::datatypes::Field::new(name, arrow::datatypes::DataType::Utf8, true)
-                })
-                .collect::<Vec<_>>(),
-        );
-
-        let columns: Vec<Arc<dyn arrow::array::Array>> = (0..self.header.len())
-            .map(|col_idx| {
-                let string_array: arrow::array::StringArray = self
-                    .current_rows
-                    .iter()
-                    .map(|row| Some(row[col_idx].as_str()))
-                    .collect();
-                Arc::new(string_array) as Arc<dyn arrow::array::Array>
-            })
-            .collect();
+        let extension = std::path::Path::new(output_path)
+            .extension()
+            .map(|e| e.to_ascii_lowercase());
+        let as_csv = match extension {
+            Some(ext) if ext == "csv" => true,
+            Some(ext) if ext == "parquet" || ext == "pqt" => false,
+            _ => {
+                return Err(format!(
+                    "unknown file type for '{}' (use .parquet, .pqt or .csv)",
+                    output_path
+                )
+                .into());
+            }
+        };
+        check_writable_dir(output_path)?;
 
-        let batch = RecordBatch::try_new(Arc::new(schema), columns)?;
+        // The result batches are written as they came back from the query,
+        // so column types and nulls are kept exactly.
+        let schema = self
+            .result_schema
+            .clone()
+            .ok_or("No SQL results to save. Execute a query first.")?;
 
         let file = File::create(output_path)?;
-        let props = WriterProperties::builder().build();
-        let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(props))?;
-        writer.write(&batch)?;
+        if as_csv {
+            let mut writer = arrow::csv::Writer::new(file);
+            for batch in &self.result_batches {
+                writer.write(&nested_as_text(batch)?)?;
+            }
+            return Ok(());
+        }
+
+        let mut props = WriterProperties::builder().set_compression(self.save_compression);
+        if let Some(size) = self.row_group_size {
+            props = props.set_max_row_group_size(size);
+        }
+        let props = props.build();
+        let mut writer = ArrowWriter::try_new(file, schema, Some(props))?;
+        for batch in &self.result_batches {
+            writer.write(batch)?;
+        }
         writer.close()?;
 
         Ok(())
     }
 
-    fn scroll_left(&mut self) {
-        if self.col_offset > 0 {
-            self.col_offset -= 1;
+    /// Puts the column cursor on `col`, scrolling just far enough to keep it
+    /// on screen.
+    fn move_cursor(&mut self, col: usize) {
+        let total_cols = self.current_rows.first().map_or(0, |r| r.len());
+        self.cursor_col = col.min(total_cols.saturating_sub(1));
+        if self.cursor_col < self.col_offset {
+            self.col_offset = self.cursor_col;
+        } else if self.cursor_col >= self.col_offset + VISIBLE_COLS {
+            self.col_offset = self.cursor_col + 1 - VISIBLE_COLS;
         }
     }
 
-    fn scroll_right(&mut self) {
-        if self.current_rows.is_empty() {
-            return;
-        }
+    /// A query can return fewer columns than the current scroll position, so
+    /// pull the offset back until a full window (or every column) is visible,
+    /// and keep the cursor inside that window.
+    fn clamp_col_offset(&mut self) {
+        let total_cols = self.current_rows.first().map_or(0, |r| r.len());
+        self.col_offset = self.col_offset.min(total_cols.saturating_sub(VISIBLE_COLS));
+        let end = (self.col_offset + VISIBLE_COLS).min(total_cols);
+        self.cursor_col = self
+            .cursor_col
+            .min(end.saturating_sub(1))
+            .max(self.col_offset);
+    }
 
-        let total_cols = self.current_rows[0].len();
-        if self.col_offset + VISIBLE_COLS < total_cols {
-            self.col_offset += 1;
+    /// Steps the LIMIT added to previews and re-runs the last query with it.
+    fn adjust_preview_limit(&mut self, increase: bool) -> Option<Action> {
+        if !self.auto_limit {
+            return None;
         }
+        self.preview_limit = if increase {
+            self.preview_limit + PREVIEW_LIMIT_STEP
+        } else {
+            // Never raise the limit on the way down, e.g. from --preview-rows 100.
+            self.preview_limit
+                .saturating_sub(PREVIEW_LIMIT_STEP)
+                .max(self.preview_limit.min(PREVIEW_LIMIT_STEP))
+        };
+
+        // Re-run the query behind the results, not whatever is in the editor now.
+        self.result_query.clone().map(Action::ExecuteSql)
     }
 
     fn handle_key_event(&mut self, key: KeyEvent) -> Option<Action> {
+        if self.show_schema_popup {
+            match key.code {
+                KeyCode::Esc | KeyCode::F(3) => self.show_schema_popup = false,
+                KeyCode::Up => self.schema_scroll = self.schema_scroll.saturating_sub(1),
+                KeyCode::Down => self.schema_scroll = self.schema_scroll.saturating_add(1),
+                _ => {}
+            }
+            return None;
+        }
+
+        if self.show_detail {
+            match key.code {
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('v') => self.show_detail = false,
+                KeyCode::Up => self.detail_scroll = self.detail_scroll.saturating_sub(1),
+                KeyCode::Down => self.detail_scroll = self.detail_scroll.saturating_add(1),
+                _ => {}
+            }
+            return None;
+        }
+
+        if self.show_cell {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('c') => self.show_cell = false,
+                KeyCode::Up => self.cell_scroll = self.cell_scroll.saturating_sub(1),
+                KeyCode::Down => self.cell_scroll = self.cell_scroll.saturating_add(1),
+                _ => {}
+            }
+            return None;
+        }
+
+        if self.show_activity {
+            match key.code {
+                KeyCode::Esc | KeyCode::F(7) => self.show_activity = false,
+                KeyCode::Up => self.activity_scroll = self.activity_scroll.saturating_sub(1),
+                KeyCode::Down => self.activity_scroll = self.activity_scroll.saturating_add(1),
+                _ => {}
+            }
+            return None;
+        }
+
+        if self.analyzed_plan.is_some() {
+            match key.code {
+                KeyCode::Esc | KeyCode::F(6) => self.analyzed_plan = None,
+                KeyCode::Up => self.plan_scroll = self.plan_scroll.saturating_sub(1),
+                KeyCode::Down => self.plan_scroll = self.plan_scroll.saturating_add(1),
+                _ => {}
+            }
+            return None;
+        }
+
         if key.code == KeyCode::Esc
             || (key.code == KeyCode::Char('q')
                 && key.modifiers.contains(event::KeyModifiers::CONTROL))
@@ -346,14 +653,50 @@ impl<'a> App<'a> {
             return None;
         }
 
+        if key.code == KeyCode::F(3) {
+            if self.result_schema.is_some() {
+                self.show_schema_popup = true;
+                self.schema_scroll = 0;
+            } else {
+                self.execution_state =
+                    ExecutionState::Error("Execute a query first to see its schema".to_string());
+            }
+            return None;
+        }
+
         if key.code == KeyCode::Char('e') && key.modifiers.contains(event::KeyModifiers::CONTROL) {
-            return Some(Action::ExecuteSql);
+            return Some(Action::ExecuteSql(self.sql_textarea.lines().join("\n")));
+        }
+
+        if key.code == KeyCode::F(7) {
+            self.show_activity = true;
+            self.activity_scroll = 0;
+            return None;
+        }
+
+        if key.code == KeyCode::F(4) {
+            return Some(Action::CountRows);
+        }
+
+        if key.code == KeyCode::F(6) {
+            return Some(Action::AnalyzeQuery);
+        }
+
+        if key.code == KeyCode::F(5) {
+            if self.original_view.is_some() {
+                self.show_split = !self.show_split;
+            } else {
+                self.execution_state =
+                    ExecutionState::Error("Execute a query first to compare".to_string());
+            }
+            return None;
         }
 
         if key.code == KeyCode::Char('r') && key.modifiers.contains(event::KeyModifiers::CONTROL) {
             if let Err(e) = self.load_original_data() {
                 self.execution_state = ExecutionState::Error(format!("Error resetting: {}", e));
             }
+            self.log("Reset to the original data".to_string());
             return None;
         }
 
@@ -361,6 +704,9 @@ impl<'a> App<'a> {
             if self.is_filtered {
                 self.show_save_dialog = true;
                 self.focused_pane = FocusedPane::SaveDialog;
+                if let Err(e) = check_writable_dir(&self.save_dialog.lines().join("")) {
+                    self.execution_state = ExecutionState::Error(e);
+                }
             } else {
                 self.execution_state =
                     ExecutionState::Error("Execute a query first before saving".to_string());
@@ -377,8 +723,25 @@ impl<'a> App<'a> {
                 KeyCode::Down => self.table_state.select_next(),
                 KeyCode::PageDown => self.load_next_batch(),
                 KeyCode::PageUp => self.load_previous_batch(),
-                KeyCode::Left => self.scroll_left(),
-                KeyCode::Right => self.scroll_right(),
+                KeyCode::Home => self.load_first_batch(),
+                KeyCode::End => self.load_last_batch(),
+                KeyCode::Left => self.move_cursor(self.cursor_col.saturating_sub(1)),
+                KeyCode::Right => self.move_cursor(self.cursor_col + 1),
+                KeyCode::Char('i') => self.show_ordinals = !self.show_ordinals,
+                KeyCode::Enter | KeyCode::Char('v') => {
+                    if self.table_state.selected().is_some() && !self.current_rows.is_empty() {
+                        self.show_detail = true;
+                        self.detail_scroll = 0;
+                    }
+                }
+                KeyCode::Char('c') => {
+                    if self.table_state.selected().is_some() && !self.current_rows.is_empty() {
+                        self.show_cell = true;
+                        self.cell_scroll = 0;
+                    }
+                }
+                KeyCode::Char('+') => return self.adjust_preview_limit(true),
+                KeyCode::Char('-') => return self.adjust_preview_limit(false),
                 _ => {}
             },
             FocusedPane::SaveDialog => match key.code {
@@ -386,6 +749,7 @@ impl<'a> App<'a> {
                     let filename = self.save_dialog.lines().join("");
                     match self.save_results(&filename) {
                         Ok(_) => {
+                            self.log(format!("Saved results to {}", filename));
                             self.execution_state = ExecutionState::Success;
                             self.show_save_dialog = false;
                             self.focused_pane = FocusedPane::SqlEditor;
@@ -402,6 +766,9 @@ impl<'a> App<'a> {
                     self.show_save_dialog = false;
                     self.focused_pane = FocusedPane::SqlEditor;
                 }
+                KeyCode::Tab => {
+                    self.save_compression = next_compression(self.save_compression);
+                }
                 _ => {
                     self.save_dialog.input(key);
                 }
@@ -412,6 +779,8 @@ impl<'a> App<'a> {
     }
 
     fn run(mut self, mut terminal: DefaultTerminal) -> Result<(), Box<dyn std::error::Error>> {
+        let runtime = tokio::runtime::Runtime::new()?;
+
         loop {
             terminal.draw(|f| self.draw(f))?;
 
@@ -422,18 +791,39 @@ impl<'a> App<'a> {
 
                 match self.handle_key_event(key) {
                     Some(Action::Quit) => return Ok(()),
-                    Some(Action::ExecuteSql) => {
+                    Some(Action::ExecuteSql(sql)) => {
                         self.execution_state = ExecutionState::Executing;
                         terminal.draw(|f| self.draw(f))?;
 
-                        let runtime = tokio::runtime::Runtime::new()?;
                         runtime.block_on(async {
-                            if let Err(e) = self.execute_sql().await {
+                            if let Err(e) = self.execute_sql(sql).await {
                                 self.execution_state =
                                     ExecutionState::Error(format!("Error: {}", e));
                             }
                         });
                     }
+                    Some(Action::CountRows) => {
+                        self.execution_state = ExecutionState::Executing;
+                        terminal.draw(|f| self.draw(f))?;
+
+                        runtime.block_on(async {
+                            if let Err(e) = self.count_result_rows().await {
+                                self.execution_state =
+                                    ExecutionState::Error(format!("Count error: {}", e));
+                            }
+                        });
+                    }
+                    Some(Action::AnalyzeQuery) => {
+                        self.execution_state = ExecutionState::Executing;
+                        terminal.draw(|f| self.draw(f))?;
+
+                        runtime.block_on(async {
+                            if let Err(e) = self.analyze_query().await {
+                                self.execution_state =
+                                    ExecutionState::Error(format!("Analyze error: {}", e));
+                            }
+                        });
+                    }
                     None => {}
                 }
             }
@@ -456,17 +846,249 @@ impl<'a> App<'a> {
                 FocusedPane::SqlEditor => Style::default().fg(Color::Cyan),
                 FocusedPane::TablePreview | FocusedPane::SaveDialog => Style::default(),
             })
-            .title("SQL Editor (F2: Switch | Ctrl+E: Execute | Ctrl+R: Reset | Ctrl+S: Save | Esc: Quit)");
+            .title("SQL Editor (F2: Switch | F3: Schema | F5: Split | F6: Analyze | F7: Activity | Ctrl+E: Execute | Ctrl+R: Reset | Ctrl+S: Save | Esc: Quit)");
 
         self.sql_textarea.set_block(sql_block);
         f.render_widget(&self.sql_textarea, chunks[0]);
 
         self.draw_status(f, chunks[1]);
-        self.draw_table(f, chunks[2]);
+
+        if self.show_split && self.original_view.is_some() {
+            let halves = Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(chunks[2]);
+            self.draw_original(f, halves[0]);
+            self.draw_table(f, halves[1]);
+        } else {
+            self.draw_table(f, chunks[2]);
+        }
 
         if self.show_save_dialog {
             self.draw_save_dialog(f, area);
         }
+
+        if self.show_schema_popup {
+            self.draw_schema_popup(f, area);
+        }
+
+        if self.analyzed_plan.is_some() {
+            self.draw_plan_popup(f, area);
+        }
+
+        if self.show_detail {
+            self.draw_detail_popup(f, area);
+        }
+
+        if self.show_activity {
+            self.draw_activity_popup(f, area);
+        }
+
+        if self.show_cell {
+            self.draw_cell_popup(f, area);
+        }
+    }
+
+    fn draw_original(&self, f: &mut Frame, area: ratatui::layout::Rect) {
+        let Some(original) = &self.original_view else {
+            return;
+        };
+
+        let tc = original.header.len();
+        let start = self.col_offset.min(tc.saturating_sub(VISIBLE_COLS));
+        let end = (start + VISIBLE_COLS).min(tc);
+
+        let hdr = Row::new(
+            (start..end)
+                .map(|c| header_cell(original.header[c].clone(), &original.column_types[c], false))
+                .collect::<Vec<_>>(),
+        )
+        .height(2);
+
+        let rows = original
+            .rows
+            .iter()
+            .map(|r| Row::new(r[start..end].iter().map(String::as_str).collect::<Vec<_>>()));
+
+        let title = format!(
+            "Original Data | Cols {}–{}/{} | Batch {} | [F5: Close split]",
+            start,
+            end.saturating_sub(1),
+            tc,
+            original.batch_idx + 1,
+        );
+
+        let widths = content_widths(
+            &original.header,
+            original.rows.iter().take(area.height as usize),
+            start,
+            end,
+            MAX_AUTO_WIDTH,
+        );
+
+        let table = Table::new(rows, widths)
+            .header(hdr)
+            .block(Block::default().borders(Borders::ALL).title(title));
+
+        f.render_widget(table, area);
+    }
+
+    fn draw_schema_popup(&self, f: &mut Frame, area: ratatui::layout::Rect) {
+        use ratatui::layout::Rect;
+        use ratatui::widgets::Clear;
+
+        let Some(schema) = &self.result_schema else {
+            return;
+        };
+
+        let lines = describe_fields(schema);
+        let height = (lines.len() as u16).saturating_add(2).min(area.height);
+        let popup_area = Rect {
+            x: area.width / 6,
+            y: (area.height - height) / 2,
+            width: area.width * 2 / 3,
+            height,
+        };
+
+        let popup = Paragraph::new(lines.join("\n"))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Result Schema (↑/↓: Scroll | F3/Esc: Close)")
+                    .border_style(Style::default().fg(Color::Green)),
+            )
+            .scroll((self.schema_scroll, 0));
+
+        f.render_widget(Clear, popup_area);
+        f.render_widget(popup, popup_area);
+    }
+
+    fn draw_detail_popup(&self, f: &mut Frame, area: ratatui::layout::Rect) {
+        use ratatui::layout::Rect;
+        use ratatui::widgets::Clear;
+
+        let selected = self.table_state.selected().unwrap_or(0);
+        let Some(row) = self.current_rows.get(selected) else {
+            return;
+        };
+
+        let lines = record_lines(&self.header, row);
+        let height = (lines.len() as u16).saturating_add(2).min(area.height);
+        let popup_area = Rect {
+            x: area.width / 6,
+            y: (area.height - height) / 2,
+            width: area.width * 2 / 3,
+            height,
+        };
+
+        let popup = Paragraph::new(lines.join("\n"))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        "Row {} (↑/↓: Scroll | v/Enter/Esc: Close)",
+                        selected
+                    ))
+                    .border_style(Style::default().fg(Color::Green)),
+            )
+            .scroll((self.detail_scroll, 0));
+
+        f.render_widget(Clear, popup_area);
+        f.render_widget(popup, popup_area);
+    }
+
+    fn draw_cell_popup(&self, f: &mut Frame, area: ratatui::layout::Rect) {
+        use ratatui::layout::Rect;
+        use ratatui::widgets::Clear;
+
+        let selected = self.table_state.selected().unwrap_or(0);
+        let Some(value) = self
+            .current_rows
+            .get(selected)
+            .and_then(|row| row.get(self.cursor_col))
+        else {
+            return;
+        };
+
+        let popup_area = Rect {
+            x: area.width / 12,
+            y: area.height / 12,
+            width: area.width * 5 / 6,
+            height: area.height * 5 / 6,
+        };
+
+        let popup = Paragraph::new(value.as_str())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        "{} at row {} (↑/↓: Scroll | c/Esc: Close)",
+                        self.header[self.cursor_col], selected
+                    ))
+                    .border_style(Style::default().fg(Color::Green)),
+            )
+            .wrap(Wrap { trim: false })
+            .scroll((self.cell_scroll, 0));
+
+        f.render_widget(Clear, popup_area);
+        f.render_widget(popup, popup_area);
+    }
+
+    fn draw_activity_popup(&self, f: &mut Frame, area: ratatui::layout::Rect) {
+        use ratatui::layout::Rect;
+        use ratatui::widgets::Clear;
+
+        let popup_area = Rect {
+            x: area.width / 12,
+            y: area.height / 12,
+            width: area.width * 5 / 6,
+            height: area.height * 5 / 6,
+        };
+
+        let text = if self.activity.is_empty() {
+            "Nothing yet. Queries, counts, saves and resets are listed here.".to_string()
+        } else {
+            self.activity.join("\n")
+        };
+        let popup = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Activity (UTC) (↑/↓: Scroll | F7/Esc: Close)")
+                    .border_style(Style::default().fg(Color::Green)),
+            )
+            .wrap(Wrap { trim: false })
+            .scroll((self.activity_scroll, 0));
+
+        f.render_widget(Clear, popup_area);
+        f.render_widget(popup, popup_area);
+    }
+
+    fn draw_plan_popup(&self, f: &mut Frame, area: ratatui::layout::Rect) {
+        use ratatui::layout::Rect;
+        use ratatui::widgets::Clear;
+
+        let Some(plan) = &self.analyzed_plan else {
+            return;
+        };
+
+        let popup_area = Rect {
+            x: area.width / 12,
+            y: area.height / 12,
+            width: area.width * 5 / 6,
+            height: area.height * 5 / 6,
+        };
+
+        let popup = Paragraph::new(plan.as_str())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("EXPLAIN ANALYZE (↑/↓: Scroll | F6/Esc: Close)")
+                    .border_style(Style::default().fg(Color::Green)),
+            )
+            .wrap(Wrap { trim: false })
+            .scroll((self.plan_scroll, 0));
+
+        f.render_widget(Clear, popup_area);
+        f.render_widget(popup, popup_area);
     }
 
     fn draw_save_dialog(&mut self, f: &mut Frame, area: ratatui::layout::Rect) {
@@ -480,6 +1102,16 @@ impl<'a> App<'a> {
             height: 5,
         };
 
+        self.save_dialog.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "Save As [{}] (Tab: Compression | Enter to save, Esc to cancel)",
+                    compression_label(self.save_compression)
+                ))
+                .border_style(Style::default().fg(Color::Green)),
+        );
+
         f.render_widget(Clear, popup_area);
         f.render_widget(&self.save_dialog, popup_area);
     }
@@ -503,17 +1135,45 @@ impl<'a> App<'a> {
                 "⏳ Executing SQL query... Please wait".to_string(),
                 Style::default().fg(Color::Magenta).bold(),
             ),
-            ExecutionState::Success => (
-                "✓ Query executed successfully".to_string(),
-                Style::default().fg(Color::Green),
-            ),
+            ExecutionState::Success => {
+                let text = match (&self.executed_sql, self.matched_rows) {
+                    (_, Some(matched)) => {
+                        format!("✓ Showing {} of {} matching rows", self.total_rows, matched)
+                    }
+                    (Some(sql), None) => match self.query_time {
+                        Some(elapsed) => format!(
+                            "✓ Query executed in {}ms, {} rows | Ran: {}",
+                            elapsed.as_millis(),
+                            format_count(self.total_rows),
+                            sql.replace('\n', " ")
+                        ),
+                        None => format!(
+                            "✓ Query executed successfully | Ran: {}",
+                            sql.replace('\n', " ")
+                        ),
+                    },
+                    (None, None) => "✓ Query executed successfully".to_string(),
+                };
+                (text, Style::default().fg(Color::Green))
+            }
             ExecutionState::Error(error) => {
                 (format!("❌ {}", error), Style::default().fg(Color::Red))
             }
         };
 
         let status = Paragraph::new(status_text)
-            .block(Block::default().borders(Borders::ALL).title("Status"))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(if self.auto_limit {
+                        format!(
+                            "Status (preview limit {}, +/- in table: adjust)",
+                            self.preview_limit
+                        )
+                    } else {
+                        "Status (no preview limit)".to_string()
+                    }),
+            )
             .style(status_style)
             .wrap(Wrap { trim: true });
 
@@ -529,6 +1189,7 @@ impl<'a> App<'a> {
             return;
         }
 
+        self.clamp_col_offset();
         let tc = self.current_rows[0].len();
         let start = self.col_offset;
         let end = (start + VISIBLE_COLS).min(tc);
@@ -536,18 +1197,39 @@ impl<'a> App<'a> {
         let hdr = Row::new(
             self.header[start..end]
                 .iter()
-                .map(|s| s.as_str())
+                .enumerate()
+                .map(|(i, name)| {
+                    let label = if self.show_ordinals {
+                        format!("{}:{}", start + i, name)
+                    } else {
+                        name.clone()
+                    };
+                    let cell = header_cell(label, &self.column_types[start + i], false);
+                    if start + i == self.cursor_col {
+                        cell.reversed()
+                    } else {
+                        cell
+                    }
+                })
                 .collect::<Vec<_>>(),
         )
-        .bold()
-        .height(1);
+        .height(2);
 
         let visible_rows = self.current_rows.iter().map(|r| {
             let slice = &r[start..end];
             Row::new(slice.iter().map(String::as_str).collect::<Vec<_>>())
         });
 
-        let widths = std::iter::repeat_n(12u16, end - start);
+        let widths = content_widths(
+            &self.header,
+            self.current_rows
+                .iter()
+                .skip(self.table_state.offset())
+                .take(area.height as usize),
+            start,
+            end,
+            MAX_AUTO_WIDTH,
+        );
 
         let data_source = if self.is_filtered {
             "SQL Results"
@@ -555,29 +1237,43 @@ impl<'a> App<'a> {
             "Original Data"
         };
 
+        // Result batches can be uneven, so count the rows before this one.
+        let batch_start_row = if self.is_filtered {
+            self.result_batches[..self.current_batch_idx]
+                .iter()
+                .map(|b| b.num_rows())
+                .sum()
+        } else {
+            self.current_batch_idx * self.batch_size
+        };
+        let batch_end_row = batch_start_row + self.current_rows.len() - 1;
+
         let title = if self.is_filtered {
-            let limit_note = if self.total_rows >= MAX_PREVIEW_ROWS {
-                format!(" (limited to {} for preview)", MAX_PREVIEW_ROWS)
-            } else {
-                String::new()
+            let limit_note = match self.matched_rows {
+                Some(matched) => format!(" of {} matched", matched),
+                None if self.auto_limit && self.total_rows >= self.preview_limit => format!(
+                    " (limited to {} for preview, F4: count all)",
+                    self.preview_limit
+                ),
+                None => String::new(),
             };
 
             format!(
-                "{} | Cols {}–{}/{} | {} rows{} | [←/→: Cols | ↑/↓: Rows]",
+                "{} | Cols {}–{}/{} | Rows {}–{}/{}{} | Batch {}/{} | [PgUp/PgDn: Batches | Home/End: First/Last batch | ←/→: Column cursor | ↑/↓: Rows | v/Enter: Record | c: Cell]",
                 data_source,
                 start,
                 end.saturating_sub(1),
                 tc,
+                batch_start_row,
+                batch_end_row,
                 self.total_rows,
                 limit_note,
+                self.current_batch_idx + 1,
+                self.total_batches,
             )
         } else {
-            let batch_start_row = self.current_batch_idx * self.batch_size;
-            let current_batch_rows = self.current_rows.len();
-            let batch_end_row = batch_start_row + current_batch_rows - 1;
-
             format!(
-                "{} | Cols {}–{}/{} | Rows {}–{}/{} | Batch {}/{} | [PgUp/PgDn: Batches | ←/→: Cols | ↑/↓: Rows]",
+                "{} | Cols {}–{}/{} | Rows {}–{}/{} | Batch {}/{} | [PgUp/PgDn: Batches | Home/End: First/Last batch | ←/→: Column cursor | ↑/↓: Rows | v/Enter: Record | c: Cell]",
                 data_source,
                 start,
                 end.saturating_sub(1),
@@ -590,13 +1286,16 @@ impl<'a> App<'a> {
             )
         };
 
-        let table_block = Block::default()
+        let mut table_block = Block::default()
             .borders(Borders::ALL)
             .border_style(match self.focused_pane {
                 FocusedPane::TablePreview => Style::default().fg(Color::Cyan),
                 FocusedPane::SqlEditor | FocusedPane::SaveDialog => Style::default(),
             })
             .title(title);
+        if let Some(notice) = &self.notice {
+            table_block = table_block.title_bottom(notice.as_str());
+        }
 
         let table = Table::new(visible_rows, widths)
             .header(hdr)
@@ -607,22 +1306,193 @@ impl<'a> App<'a> {
     }
 }
 
+/// Checks that the directory a file would be saved to exists and accepts new
+/// files, by creating and removing a probe file there. This turns a late,
+/// generic OS error into a clear message.
+fn check_writable_dir(output_path: &str) -> Result<(), String> {
+    let dir = match std::path::Path::new(output_path).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => std::path::Path::new("."),
+    };
+    if !dir.is_dir() {
+        return Err(format!("Directory does not exist: {}", dir.display()));
+    }
+
+    let probe = dir.join(format!(".pq-peak-write-check-{}", std::process::id()));
+    match std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+    {
+        Ok(_) => {
+            let _ = std::fs::remove_file(&probe);
+            Ok(())
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            Err(format!("Directory not writable: {}", dir.display()))
+        }
+        Err(e) => Err(format!("Cannot write to {}: {}", dir.display(), e)),
+    }
+}
+
+fn next_compression(compression: Compression) -> Compression {
+    match compression {
+        Compression::UNCOMPRESSED => Compression::SNAPPY,
+        Compression::SNAPPY => Compression::ZSTD(ZstdLevel::default()),
+        Compression::ZSTD(_) => Compression::GZIP(GzipLevel::default()),
+        _ => Compression::UNCOMPRESSED,
+    }
+}
+
+fn compression_label(compression: Compression) -> &'static str {
+    match compression {
+        Compression::UNCOMPRESSED => "Uncompressed",
+        Compression::SNAPPY => "SNAPPY",
+        Compression::ZSTD(_) => "ZSTD",
+        Compression::GZIP(_) => "GZIP",
+        _ => "Other",
+    }
+}
+
+/// A DataFusion session with the file registered as the `data` table.
+pub async fn session_context(
+    file_path: &PathBuf,
+    batch_size: usize,
+) -> Result<SessionContext, Box<dyn std::error::Error>> {
+    let config = SessionConfig::new()
+        .with_target_partitions(1)
+        .with_batch_size(batch_size);
+    let ctx = SessionContext::new_with_config(config);
+
+    let path = file_path.to_str().ok_or("file path is not valid UTF-8")?;
+    // Files are matched on extension, so register the one this file has.
+    let extension = format!(
+        ".{}",
+        file_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+    );
+    match TextFormat::from_path(file_path) {
+        Some(TextFormat::Csv) => {
+            let options = CsvReadOptions::new()
+                .has_header(true)
+                .file_extension(&extension);
+            ctx.register_csv("data", path, options).await?;
+        }
+        Some(TextFormat::Json) => {
+            let options = NdJsonReadOptions::default().file_extension(&extension);
+            ctx.register_json("data", path, options).await?;
+        }
+        None => {
+            ctx.register_parquet("data", path, ParquetReadOptions::default())
+                .await?;
+        }
+    }
+
+    Ok(ctx)
+}
+
+/// Schema, row count and first batch of the file being edited, plus a note
+/// about how it was decoded.
+fn open_original(
+    file_path: &PathBuf,
+    batch_size: usize,
+) -> Result<(SchemaRef, usize, RecordBatch, Option<String>), Box<dyn std::error::Error>> {
+    if let Some(format) = TextFormat::from_path(file_path) {
+        let schema = source::infer_schema(file_path, format)?;
+        let total_rows = source::count_rows(file_path, format, &schema)?;
+        let first_batch = source::read_batch(file_path, format, &schema, batch_size, None, 0)?
+            .ok_or("No data in file")?;
+        return Ok((schema, total_rows, first_batch, None));
+    }
+
+    let file = File::open(file_path)?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?.with_batch_size(batch_size);
+
+    let metadata = builder.metadata();
+    let total_rows = metadata.file_metadata().num_rows() as usize;
+    let notice = int96_notice(metadata.file_metadata().schema_descr());
+    let schema = builder.schema().clone();
+
+    let first_batch = builder.build()?.next().ok_or("No data in file")??;
+    Ok((schema, total_rows, first_batch, notice))
+}
+
+/// Reads one batch of the original file. `schema` is the one `open_original`
+/// inferred for CSV and JSON files, so paging doesn't infer it again.
+fn read_original_batch(
+    file_path: &PathBuf,
+    schema: &SchemaRef,
+    batch_size: usize,
+    batch_idx: usize,
+) -> Result<Option<RecordBatch>, Box<dyn std::error::Error>> {
+    if let Some(format) = TextFormat::from_path(file_path) {
+        return source::read_batch(file_path, format, schema, batch_size, None, batch_idx);
+    }
+
+    let file = File::open(file_path)?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?.with_batch_size(batch_size);
+    let batch = builder.build()?.nth(batch_idx).transpose()?;
+    Ok(batch)
+}
+
+fn column_types(schema: &Schema) -> Vec<String> {
+    schema
+        .fields()
+        .iter()
+        .map(|f| f.data_type().to_string())
+        .collect()
+}
+
+fn new_sql_textarea<'a>(sql: &str) -> TextArea<'a> {
+    let mut textarea = TextArea::from(sql.lines());
+    textarea.set_block(Block::default().borders(Borders::ALL).title("SQL Editor"));
+    textarea
+}
+
 enum Action {
     Quit,
-    ExecuteSql,
+    ExecuteSql(String),
+    CountRows,
+    AnalyzeQuery,
 }
 
-pub fn edit(file_path: &PathBuf, batch_size: usize) -> Result<(), Box<dyn std::error::Error>> {
-    let valid = validate_extension(file_path);
+pub fn edit(
+    file_path: &PathBuf,
+    batch_size: usize,
+    sql_file: Option<&PathBuf>,
+    row_group_size: Option<usize>,
+    preview_rows: usize,
+    auto_limit: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let valid = validate_extension(file_path) || TextFormat::from_path(file_path).is_some();
     if !valid {
         eprintln!("ERROR: {}", PeakError::UnsupportedFileType);
         std::process::exit(0);
     }
 
+    let initial_sql = match sql_file {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| format!("--sql-file {}: {}", path.display(), e))?,
+        None => DEFAULT_SQL.to_string(),
+    };
+
     color_eyre::install()?;
 
+    // Open the file before taking over the terminal, so startup errors are
+    // printed normally instead of leaving the terminal in raw mode.
+    let app = App::new(
+        file_path.clone(),
+        batch_size,
+        initial_sql,
+        row_group_size,
+        preview_rows,
+        auto_limit,
+    )?;
+
     let terminal = ratatui::init();
-    let app_result = App::new(file_path.clone(), batch_size)?.run(terminal);
+    let app_result = app.run(terminal);
     ratatui::restore();
 
     app_result
@@ -1,80 +1,1143 @@
-use std::{fs::File, path::PathBuf};
+use std::{
+    cmp::Ordering, collections::HashMap, fs::File, path::PathBuf, sync::Arc, time::SystemTime,
+};
 
-use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use arrow::{
+    array::{Array, ArrayRef, RecordBatch},
+    compute::{SortOptions, cast, sort_to_indices, sum},
+    datatypes::{DataType, Schema, SchemaRef},
+    error::ArrowError,
+    util::display::array_value_to_string,
+};
+use futures::StreamExt;
+use parquet::{
+    arrow::{
+        ProjectionMask,
+        arrow_reader::{
+            ArrowReaderMetadata, ArrowReaderOptions, ParquetRecordBatchReaderBuilder,
+            statistics::StatisticsConverter,
+        },
+        async_reader::ParquetRecordBatchStreamBuilder,
+    },
+    errors::ParquetError,
+    file::metadata::RowGroupMetaData,
+    schema::types::SchemaDescriptor,
+};
 use ratatui::{
     DefaultTerminal, Frame,
-    crossterm::event::{self, Event, KeyCode, KeyEventKind},
-    style::{Style, Stylize},
-    widgets::{Block, Borders, Row, Table, TableState},
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    layout::Rect,
+    style::{Color, Style, Stylize},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState, Wrap},
 };
+use tokio::runtime::Runtime;
+use tui_textarea::TextArea;
 
-use crate::peak::batch_to_rows;
+use crate::{
+    expr::{Condition, Expr, parse_condition, parse_definition},
+    peak::{
+        NULL_SENTINEL, PeakOptions, batch_to_rows, binary_strings, lossy_strings,
+        prefix_dictionary_keys, truncate_nested,
+    },
+    source::{self, TextFormat},
+    utils::{
+        all_null_columns, content_widths, format_bytes, format_count, header_cell, int96_notice,
+        markdown_table, record_lines,
+    },
+};
 
 const VISIBLE_COLS: usize = 10;
+const MAX_AUTO_WIDTH: usize = 40;
+const MAX_FIT_WIDTH: u16 = 50;
+
+enum PromptKind {
+    ComputedColumn,
+    Mask,
+    GotoRow,
+    Search,
+    Range,
+    ExportMarkdown,
+}
+
+/// Where batches come from. CSV and JSON files have no footer, so there
+/// are no row groups or statistics to use.
+enum Source {
+    Parquet(ArrowReaderMetadata),
+    Text(TextFormat, SchemaRef),
+}
+
+/// Shows only rows whose value in `col` parses as a number within
+/// `min..=max`.
+struct RangeFilter {
+    col: usize,
+    min: f64,
+    max: f64,
+}
+
+impl RangeFilter {
+    fn contains(&self, row: &[String]) -> bool {
+        row[self.col]
+            .parse::<f64>()
+            .is_ok_and(|v| self.min <= v && v <= self.max)
+    }
+}
+
+/// Parses `MIN MAX` or `MIN..MAX`; either bound may be left out of the
+/// `..` form to leave that side open.
+fn parse_range(input: &str) -> Result<(f64, f64), String> {
+    let (min, max) = match input.split_once("..") {
+        Some(bounds) => bounds,
+        None => input
+            .split_once(char::is_whitespace)
+            .ok_or("expected MIN MAX or MIN..MAX, e.g. 100..500")?,
+    };
+    let bound = |text: &str, open: f64| -> Result<f64, String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(open);
+        }
+        text.parse()
+            .map_err(|_| format!("'{}' is not a number", text))
+    };
+
+    Ok((bound(min, f64::NEG_INFINITY)?, bound(max, f64::INFINITY)?))
+}
+
+/// Hides rows that repeat the row above, so group boundaries in sorted
+/// data stand out.
+#[derive(Clone, Copy, PartialEq)]
+enum DedupeMode {
+    Off,
+    CursorColumn,
+    AllColumns,
+}
+
+struct Prompt {
+    kind: PromptKind,
+    input: TextArea<'static>,
+}
 
 struct App {
     table_state: TableState,
     file_path: PathBuf,
+    options: PeakOptions,
+    file_stamp: Option<(SystemTime, u64)>,
     current_batch_idx: usize,
+    current_batch: Option<RecordBatch>,
     current_rows: Vec<Vec<String>>,
+    current_nulls: Vec<Vec<bool>>,
     header: Vec<String>,
     col_offset: usize,
+    cursor_col: usize,
+    show_ordinals: bool,
+    freeze_first: bool,
+    show_null_footer: bool,
+    dedupe: DedupeMode,
+    sort_col: Option<usize>,
+    sort_desc: bool,
+    nulls_first: bool,
+    row_order: Vec<usize>,
+    range: Option<RangeFilter>,
+    col_widths: HashMap<usize, u16>,
     batch_size: usize,
     total_batches: usize,
     total_rows: usize,
+    read_columns: Option<Vec<usize>>,
+    source: Source,
+    row_groups: Option<Vec<usize>>,
+    casts: Vec<(String, DataType)>,
+    runtime: Option<Runtime>,
+    computed: Vec<Expr>,
+    mask: Option<Condition>,
+    profiles: HashMap<usize, String>,
+    all_null_cols: Vec<usize>,
+    io_bytes: (u64, u64),
+    search: Option<String>,
+    show_detail: bool,
+    detail_scroll: u16,
+    show_cell: bool,
+    cell_scroll: u16,
+    prompt: Option<Prompt>,
+    message: Option<String>,
+    notices: Vec<String>,
+}
+
+/// Compares non-null cells with numbers before text: numbers by value, text
+/// lexically. Ranking the two kinds keeps the order total when a column
+/// mixes them, which `sort_by` needs.
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(x), Ok(y)) => x.total_cmp(&y),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+/// Which cells of each row are null, from the batch's validity rather than
+/// the rendered text, so a string that reads "NULL" is still a value.
+fn null_rows(batch: &RecordBatch) -> Vec<Vec<bool>> {
+    let nulls: Vec<_> = batch.columns().iter().map(|c| c.logical_nulls()).collect();
+    (0..batch.num_rows())
+        .map(|i| {
+            nulls
+                .iter()
+                .map(|n| n.as_ref().is_some_and(|n| n.is_null(i)))
+                .collect()
+        })
+        .collect()
+}
+
+/// The cell text and null flag for a computed or mask value.
+fn derived_cell(value: Option<String>) -> (String, bool) {
+    match value {
+        Some(value) => (value, false),
+        None => (NULL_SENTINEL.to_string(), true),
+    }
+}
+
+/// Case-insensitive substring match on any cell; `needle` is already
+/// lowercase.
+fn row_matches(row: &[String], needle: &str) -> bool {
+    row.iter().any(|cell| cell.to_lowercase().contains(needle))
+}
+
+/// Invalid UTF-8 in a string column fails the whole batch read with this
+/// general error from the Parquet decoder. The sync reader passes it on as
+/// `ArrowError::ParquetError` and the async reader as `ParquetError`.
+fn is_utf8_error(e: &(dyn std::error::Error + 'static)) -> bool {
+    const INVALID_UTF8: &str = "encountered non UTF-8 data";
+    if let Some(ArrowError::ParquetError(message)) = e.downcast_ref::<ArrowError>() {
+        return message.contains(INVALID_UTF8);
+    }
+    if let Some(ParquetError::General(message)) = e.downcast_ref::<ParquetError>() {
+        return message.contains(INVALID_UTF8);
+    }
+    false
+}
+
+/// Modification time and size, used to notice the file being rewritten
+/// while it is open.
+fn file_stamp(path: &PathBuf) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Smallest (or largest) non-null value among per-row-group statistics.
+fn extreme(values: &ArrayRef, largest: bool) -> Result<String, ArrowError> {
+    let options = SortOptions {
+        descending: largest,
+        nulls_first: false,
+    };
+    let indices = sort_to_indices(values, Some(options), Some(1))?;
+    match indices.values().first() {
+        Some(&i) if values.is_valid(i as usize) => array_value_to_string(values, i as usize),
+        _ => Ok("?".to_string()),
+    }
+}
+
+/// Type, null count and min/max of one file column, taken from row group
+/// statistics so no data is read.
+fn column_profile(
+    reader_metadata: &ArrowReaderMetadata,
+    col: usize,
+    row_groups: Option<&[usize]>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let schema = reader_metadata.schema();
+    let field = schema.field(col);
+    let metadata = reader_metadata.metadata();
+    let groups: Vec<&RowGroupMetaData> = match row_groups {
+        Some(indices) => indices.iter().map(|&i| metadata.row_group(i)).collect(),
+        None => metadata.row_groups().iter().collect(),
+    };
+
+    let converter =
+        StatisticsConverter::try_new(field.name(), schema, reader_metadata.parquet_schema())?;
+    let null_counts = converter.row_group_null_counts(groups.iter().copied())?;
+    let nulls = if null_counts.null_count() > 0 {
+        "?".to_string()
+    } else {
+        format_count(sum(&null_counts).unwrap_or(0) as usize)
+    };
+    let min = extreme(&converter.row_group_mins(groups.iter().copied())?, false)?;
+    let max = extreme(&converter.row_group_maxes(groups.iter().copied())?, true)?;
+
+    Ok(format!(
+        "{}: {} | nulls {} | min {} | max {}",
+        field.name(),
+        field.data_type(),
+        nulls,
+        min,
+        max
+    ))
+}
+
+fn projection(
+    parquet_schema: &SchemaDescriptor,
+    columns: Option<&[usize]>,
+) -> Option<ProjectionMask> {
+    columns.map(|columns| ProjectionMask::roots(parquet_schema, columns.iter().copied()))
+}
+
+/// Compressed and uncompressed column chunk bytes behind one batch read.
+/// Each read opens the file again and decodes from the start of the
+/// selection, so every row group up to the batch's last row is fetched.
+fn batch_io_bytes(
+    reader_metadata: &ArrowReaderMetadata,
+    row_groups: Option<&[usize]>,
+    columns: Option<&[usize]>,
+    rows_through: usize,
+) -> (u64, u64) {
+    let metadata = reader_metadata.metadata();
+    let mask = projection(reader_metadata.parquet_schema(), columns);
+    let groups: Vec<usize> = match row_groups {
+        Some(indices) => indices.to_vec(),
+        None => (0..metadata.num_row_groups()).collect(),
+    };
+
+    let (mut compressed, mut uncompressed, mut rows) = (0, 0, 0);
+    for idx in groups {
+        if rows >= rows_through {
+            break;
+        }
+        let group = metadata.row_group(idx);
+        rows += group.num_rows() as usize;
+        for (leaf, column) in group.columns().iter().enumerate() {
+            if mask.as_ref().is_none_or(|m| m.leaf_included(leaf)) {
+                compressed += column.compressed_size() as u64;
+                uncompressed += column.uncompressed_size() as u64;
+            }
+        }
+    }
+
+    (compressed, uncompressed)
+}
+
+/// Reuses the footer parsed in `App::new`; decoding it again on every
+/// batch is slow for files with very wide schemas.
+fn open_builder(
+    file_path: &PathBuf,
+    reader_metadata: &ArrowReaderMetadata,
+    batch_size: usize,
+    columns: Option<&[usize]>,
+    row_groups: Option<&[usize]>,
+) -> Result<ParquetRecordBatchReaderBuilder<File>, Box<dyn std::error::Error>> {
+    let file = File::open(file_path)?;
+    let mut builder =
+        ParquetRecordBatchReaderBuilder::new_with_metadata(file, reader_metadata.clone())
+            .with_batch_size(batch_size);
+
+    if let Some(row_groups) = row_groups {
+        builder = builder.with_row_groups(row_groups.to_vec());
+    }
+
+    if let Some(mask) = projection(builder.parquet_schema(), columns) {
+        builder = builder.with_projection(mask);
+    }
+
+    Ok(builder)
+}
+
+/// Same read as the sync path, but through the async stream reader, which
+/// overlaps IO with decoding and helps on network filesystems.
+async fn read_batch_async(
+    file_path: &PathBuf,
+    reader_metadata: &ArrowReaderMetadata,
+    batch_size: usize,
+    columns: Option<&[usize]>,
+    row_groups: Option<&[usize]>,
+    batch_idx: usize,
+) -> Result<Option<RecordBatch>, Box<dyn std::error::Error>> {
+    let file = tokio::fs::File::open(file_path).await?;
+    let mut builder =
+        ParquetRecordBatchStreamBuilder::new_with_metadata(file, reader_metadata.clone())
+            .with_batch_size(batch_size);
+
+    if let Some(row_groups) = row_groups {
+        builder = builder.with_row_groups(row_groups.to_vec());
+    }
+
+    if let Some(mask) = projection(builder.parquet_schema(), columns) {
+        builder = builder.with_projection(mask);
+    }
+
+    let mut stream = Box::pin(builder.build()?.skip(batch_idx));
+    let batch = stream.next().await.transpose()?;
+
+    Ok(batch)
 }
 
 impl App {
-    fn new(file_path: PathBuf, batch_size: usize) -> Result<Self, Box<dyn std::error::Error>> {
-        let file = File::open(&file_path)?;
-        let builder = ParquetRecordBatchReaderBuilder::try_new(file)?.with_batch_size(batch_size);
+    fn new(file_path: PathBuf, options: PeakOptions) -> Result<Self, Box<dyn std::error::Error>> {
+        let stamp = file_stamp(&file_path);
+        let PeakOptions {
+            batch_size,
+            max_columns,
+            columns,
+            async_reader,
+            fast,
+            casts,
+            rg_filter,
+            ..
+        } = options.clone();
+        let source = match TextFormat::from_path(&file_path) {
+            Some(format) => Source::Text(format, source::infer_schema(&file_path, format)?),
+            None => Source::Parquet(ArrowReaderMetadata::load(
+                &File::open(&file_path)?,
+                ArrowReaderOptions::default(),
+            )?),
+        };
 
-        let metadata = builder.metadata();
-        let total_rows = metadata.file_metadata().num_rows() as usize;
+        let mut notices: Vec<String> = Vec::new();
+        let mut row_groups: Option<Vec<usize>> = None;
+        let mut all_null: Vec<String> = Vec::new();
+        let (total_rows, arrow_schema) = match &source {
+            Source::Parquet(reader_metadata) => {
+                let metadata = reader_metadata.metadata();
+                notices.extend(int96_notice(metadata.file_metadata().schema_descr()));
+
+                if let Some(filter) = &rg_filter {
+                    let matching = filter
+                        .select_row_groups(reader_metadata.schema(), metadata)
+                        .map_err(|e| format!("--rg-filter {}: {}", filter, e))?;
+                    if matching.is_empty() {
+                        return Err(
+                            format!("No row groups can match --rg-filter {}", filter).into()
+                        );
+                    }
+                    notices.push(format!(
+                        "{} of {} row groups match {} (--rg-filter)",
+                        matching.len(),
+                        metadata.num_row_groups(),
+                        filter
+                    ));
+                    row_groups = Some(matching);
+                }
+                if fast && metadata.num_row_groups() > 0 {
+                    notices.push("Fast preview: first row group only (--fast)".to_string());
+                    let first = row_groups.as_ref().map_or(0, |groups| groups[0]);
+                    row_groups = Some(vec![first]);
+                }
+                all_null = all_null_columns(metadata);
+
+                // With a row group selection only those groups are paged
+                // through, so the totals come from their row counts rather
+                // than the file's.
+                let total_rows = match &row_groups {
+                    Some(groups) => groups
+                        .iter()
+                        .map(|&idx| metadata.row_group(idx).num_rows() as usize)
+                        .sum(),
+                    None => metadata.file_metadata().num_rows() as usize,
+                };
+                (total_rows, reader_metadata.schema().clone())
+            }
+            Source::Text(format, schema) => {
+                if rg_filter.is_some() || fast {
+                    notices.push("--rg-filter and --fast only apply to Parquet files".to_string());
+                }
+                (
+                    source::count_rows(&file_path, *format, schema)?,
+                    schema.clone(),
+                )
+            }
+        };
         let total_batches = (total_rows + batch_size - 1) / batch_size;
 
-        let arrow_schema = builder.schema();
-        let header: Vec<String> = arrow_schema
-            .fields()
+        // Top-level columns to read, in file order; `None` reads them all.
+        let total_cols = arrow_schema.fields().len();
+        // Resolved here, before the terminal is taken over, so a typo is
+        // reported on stderr along with the names that do exist.
+        let read_columns: Option<Vec<usize>> = if !columns.is_empty() {
+            let mut indices = columns
+                .iter()
+                .map(|name| {
+                    arrow_schema.index_of(name.trim()).map_err(|_| {
+                        let available: Vec<&str> = arrow_schema
+                            .fields()
+                            .iter()
+                            .map(|f| f.name().as_str())
+                            .collect();
+                        format!(
+                            "No column named '{}' (--columns); available: {}",
+                            name.trim(),
+                            available.join(", ")
+                        )
+                    })
+                })
+                .collect::<Result<Vec<usize>, String>>()?;
+            indices.sort_unstable();
+            indices.dedup();
+            notices.push(format!(
+                "Showing {} of {} columns (--columns)",
+                indices.len(),
+                total_cols
+            ));
+            Some(indices)
+        } else {
+            match max_columns {
+                Some(max) if max < total_cols => {
+                    notices.push(format!(
+                        "Showing first {} of {} columns (--max-columns)",
+                        max, total_cols
+                    ));
+                    Some((0..max).collect())
+                }
+                _ => None,
+            }
+        };
+
+        let header: Vec<String> = match &read_columns {
+            Some(indices) => indices
+                .iter()
+                .map(|&i| arrow_schema.field(i).name().to_owned())
+                .collect(),
+            None => arrow_schema
+                .fields()
+                .iter()
+                .map(|f| f.name().to_owned())
+                .collect(),
+        };
+
+        let all_null_cols: Vec<usize> = all_null
             .iter()
-            .map(|f| f.name().to_owned())
+            .filter_map(|name| header.iter().position(|h| h == name))
             .collect();
+        if !all_null.is_empty() {
+            notices.push(format!("All-null columns: {}", all_null.join(", ")));
+        }
+
+        for (name, _) in &casts {
+            if !header.contains(name) {
+                notices.push(format!("Unknown --cast column '{}'", name));
+            }
+        }
 
-        let mut reader = builder.build()?;
-        let first_batch = reader.next().ok_or("No data in file")??;
-        let current_rows = batch_to_rows(&first_batch);
+        let runtime = if async_reader {
+            Some(Runtime::new()?)
+        } else {
+            None
+        };
 
-        Ok(Self {
+        let freeze_first = options.freeze_first;
+        let transpose = options.transpose;
+        let mut app = Self {
             table_state: TableState::default().with_selected(0),
             file_path,
+            options,
+            file_stamp: stamp,
             current_batch_idx: 0,
-            current_rows,
+            current_batch: None,
+            current_rows: Vec::new(),
+            current_nulls: Vec::new(),
             header,
             col_offset: 0,
+            cursor_col: 0,
+            show_ordinals: false,
+            freeze_first,
+            show_null_footer: false,
+            dedupe: DedupeMode::Off,
+            sort_col: None,
+            sort_desc: false,
+            nulls_first: false,
+            row_order: Vec::new(),
+            range: None,
+            col_widths: HashMap::new(),
             batch_size,
             total_batches,
             total_rows,
-        })
+            read_columns,
+            source,
+            row_groups,
+            casts,
+            runtime,
+            computed: Vec::new(),
+            mask: None,
+            profiles: HashMap::new(),
+            all_null_cols,
+            io_bytes: (0, 0),
+            search: None,
+            show_detail: transpose,
+            detail_scroll: 0,
+            show_cell: false,
+            cell_scroll: 0,
+            prompt: None,
+            message: None,
+            notices,
+        };
+
+        let first_batch = app
+            .read_batch(0)?
+            .ok_or_else(|| format!("No data in {}", app.file_path.display()))?;
+        app.set_batch(first_batch);
+        app.count_io(0);
+
+        Ok(app)
     }
 
-    fn load_batch(&mut self, batch_idx: usize) -> Result<(), Box<dyn std::error::Error>> {
-        let file = File::open(&self.file_path)?;
-        let builder =
-            ParquetRecordBatchReaderBuilder::try_new(file)?.with_batch_size(self.batch_size);
+    fn read_batch(
+        &self,
+        batch_idx: usize,
+    ) -> Result<Option<RecordBatch>, Box<dyn std::error::Error>> {
+        let reader_metadata = match &self.source {
+            Source::Parquet(reader_metadata) => reader_metadata,
+            Source::Text(format, schema) => {
+                return source::read_batch(
+                    &self.file_path,
+                    *format,
+                    schema,
+                    self.batch_size,
+                    self.read_columns.as_deref(),
+                    batch_idx,
+                );
+            }
+        };
 
-        let reader = builder.build()?;
-        let mut skipped_reader = reader.skip(batch_idx);
+        match self.read_parquet_batch(reader_metadata, batch_idx) {
+            // Read the string columns as bytes and decode them lossily, so
+            // the bad values are shown instead of the batch failing.
+            Err(e) if is_utf8_error(e.as_ref()) => {
+                let schema = reader_metadata.schema();
+                let options =
+                    ArrowReaderOptions::new().with_schema(Arc::new(binary_strings(schema)));
+                let bytes_metadata =
+                    ArrowReaderMetadata::try_new(reader_metadata.metadata().clone(), options)?;
+                let batch = self.read_parquet_batch(&bytes_metadata, batch_idx)?;
+                Ok(batch.map(|b| lossy_strings(&b, schema)).transpose()?)
+            }
+            result => result,
+        }
+    }
 
-        if let Some(batch_result) = skipped_reader.next() {
-            let batch = batch_result?;
-            self.current_rows = batch_to_rows(&batch);
+    fn read_parquet_batch(
+        &self,
+        reader_metadata: &ArrowReaderMetadata,
+        batch_idx: usize,
+    ) -> Result<Option<RecordBatch>, Box<dyn std::error::Error>> {
+        if let Some(runtime) = &self.runtime {
+            return runtime.block_on(read_batch_async(
+                &self.file_path,
+                reader_metadata,
+                self.batch_size,
+                self.read_columns.as_deref(),
+                self.row_groups.as_deref(),
+                batch_idx,
+            ));
+        }
+
+        let builder = open_builder(
+            &self.file_path,
+            reader_metadata,
+            self.batch_size,
+            self.read_columns.as_deref(),
+            self.row_groups.as_deref(),
+        )?;
+        let batch = builder.build()?.skip(batch_idx).next().transpose()?;
+
+        Ok(batch)
+    }
+
+    fn load_batch(&mut self, batch_idx: usize) -> Result<(), Box<dyn std::error::Error>> {
+        // The cached footer no longer describes the file, so reading with it
+        // could return inconsistent data or fail part way.
+        if file_stamp(&self.file_path) != self.file_stamp {
+            self.message = Some("File changed on disk; press r to reload".to_string());
+            return Ok(());
+        }
+
+        if let Some(batch) = self.read_batch(batch_idx)? {
+            self.set_batch(batch);
             self.current_batch_idx = batch_idx;
-            self.table_state.select(Some(0));
+            self.clamp_selection();
+            self.count_io(batch_idx);
         }
 
         Ok(())
     }
 
+    /// Adds a batch read to the running totals shown with `--debug-io`.
+    fn count_io(&mut self, batch_idx: usize) {
+        let Source::Parquet(reader_metadata) = &self.source else {
+            return;
+        };
+        let (compressed, uncompressed) = batch_io_bytes(
+            reader_metadata,
+            self.row_groups.as_deref(),
+            self.read_columns.as_deref(),
+            (batch_idx + 1) * self.batch_size,
+        );
+        self.io_bytes.0 += compressed;
+        self.io_bytes.1 += uncompressed;
+    }
+
+    /// Applies the `--cast` overrides. A column that fails to cast is left
+    /// as stored and the error is shown in the status line.
+    fn apply_casts(&mut self, batch: RecordBatch) -> RecordBatch {
+        if self.casts.is_empty() {
+            return batch;
+        }
+
+        let schema = batch.schema();
+        let mut fields: Vec<_> = schema.fields().iter().cloned().collect();
+        let mut columns = batch.columns().to_vec();
+
+        for (name, data_type) in &self.casts {
+            let Ok(idx) = schema.index_of(name) else {
+                continue;
+            };
+            match cast(&columns[idx], data_type) {
+                Ok(array) => {
+                    columns[idx] = array;
+                    fields[idx] = Arc::new(
+                        fields[idx]
+                            .as_ref()
+                            .clone()
+                            .with_data_type(data_type.clone()),
+                    );
+                }
+                Err(e) => {
+                    self.message = Some(format!("Cannot cast '{}' to {}: {}", name, data_type, e));
+                }
+            }
+        }
+
+        let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+        RecordBatch::try_new(Arc::new(schema), columns).unwrap_or(batch)
+    }
+
+    fn set_batch(&mut self, batch: RecordBatch) {
+        let batch = self.apply_casts(batch);
+        self.current_rows = batch_to_rows(&batch);
+        self.current_nulls = null_rows(&batch);
+        if self.options.dict_keys {
+            prefix_dictionary_keys(&batch, &mut self.current_rows);
+        }
+        if let Some(max) = self.options.max_nested_chars {
+            truncate_nested(&batch, &mut self.current_rows, max);
+        }
+        self.current_batch = Some(batch);
+
+        for expr in &self.computed {
+            for (row, nulls) in self.current_rows.iter_mut().zip(&mut self.current_nulls) {
+                let (value, null) = derived_cell(expr.eval_to_string(row, nulls));
+                row.push(value);
+                nulls.push(null);
+            }
+        }
+
+        if let Some(mask) = &self.mask {
+            for (row, nulls) in self.current_rows.iter_mut().zip(&mut self.current_nulls) {
+                let (value, null) = derived_cell(mask.eval_to_string(row, nulls));
+                row.push(value);
+                nulls.push(null);
+            }
+        }
+
+        self.resort();
+    }
+
+    /// Recomputes the display order of the loaded rows from the sort column.
+    /// Rows keep their batch positions so row numbers stay correct. Nulls go
+    /// last (or first) in either direction, like SQL's NULLS LAST/FIRST.
+    fn resort(&mut self) {
+        self.row_order = (0..self.current_rows.len()).collect();
+        if let Some(col) = self.sort_col {
+            let (rows, nulls) = (&self.current_rows, &self.current_nulls);
+            let null_side = if self.nulls_first {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            };
+            self.row_order
+                .sort_by(|&a, &b| match (nulls[a][col], nulls[b][col]) {
+                    (true, true) => Ordering::Equal,
+                    (true, false) => null_side,
+                    (false, true) => null_side.reverse(),
+                    (false, false) => {
+                        let ordering = compare_cells(&rows[a][col], &rows[b][col]);
+                        if self.sort_desc {
+                            ordering.reverse()
+                        } else {
+                            ordering
+                        }
+                    }
+                });
+        }
+    }
+
+    fn toggle_nulls_first(&mut self) {
+        self.nulls_first = !self.nulls_first;
+        self.resort();
+        self.table_state.select(Some(0));
+    }
+
+    /// Cycles the cursor column through ascending, descending and unsorted.
+    fn toggle_sort(&mut self) {
+        let cursor = self.cursor_col;
+        (self.sort_col, self.sort_desc) = match (self.sort_col, self.sort_desc) {
+            (Some(col), false) if col == cursor => (Some(cursor), true),
+            (Some(col), true) if col == cursor => (None, false),
+            _ => (Some(cursor), false),
+        };
+        self.resort();
+        self.table_state.select(Some(0));
+    }
+
+    /// End of the file and computed columns; the mask, if any, comes after.
+    fn derived_end(&self) -> usize {
+        self.header.len() - usize::from(self.mask.is_some())
+    }
+
+    fn add_computed_column(&mut self, definition: &str) {
+        let end = self.derived_end();
+        let (name, expr) = match parse_definition(definition, &self.header[..end]) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                self.message = Some(format!("Computed column error: {}", e));
+                return;
+            }
+        };
+
+        for (row, nulls) in self.current_rows.iter_mut().zip(&mut self.current_nulls) {
+            let (value, null) = derived_cell(expr.eval_to_string(row, nulls));
+            row.insert(end, value);
+            nulls.insert(end, null);
+        }
+        self.header.insert(end, name);
+        self.computed.push(expr);
+        // The mask moved one place right.
+        self.sort_col = self
+            .sort_col
+            .map(|col| if col >= end { col + 1 } else { col });
+        if self.cursor_col >= end {
+            self.cursor_col += 1;
+        }
+    }
+
+    /// Removes computed columns and the mask, which may refer to them.
+    fn clear_computed_columns(&mut self) {
+        self.remove_mask();
+
+        let file_cols = self.header.len() - self.computed.len();
+        self.header.truncate(file_cols);
+        for row in &mut self.current_rows {
+            row.truncate(file_cols);
+        }
+        for nulls in &mut self.current_nulls {
+            nulls.truncate(file_cols);
+        }
+        self.computed.clear();
+        self.col_offset = self.col_offset.min(file_cols.saturating_sub(VISIBLE_COLS));
+        self.forget_removed_columns();
+    }
+
+    fn set_mask(&mut self, condition: &str) {
+        let end = self.derived_end();
+        let mask = match parse_condition(condition, &self.header[..end]) {
+            Ok(mask) => mask,
+            Err(e) => {
+                self.message = Some(format!("Mask error: {}", e));
+                return;
+            }
+        };
+
+        self.remove_mask();
+        for (row, nulls) in self.current_rows.iter_mut().zip(&mut self.current_nulls) {
+            let (value, null) = derived_cell(mask.eval_to_string(row, nulls));
+            row.push(value);
+            nulls.push(null);
+        }
+        self.header.push(condition.to_string());
+        self.mask = Some(mask);
+    }
+
+    fn remove_mask(&mut self) {
+        if self.mask.take().is_none() {
+            return;
+        }
+
+        self.header.pop();
+        for row in &mut self.current_rows {
+            row.pop();
+        }
+        for nulls in &mut self.current_nulls {
+            nulls.pop();
+        }
+        self.col_offset = self
+            .col_offset
+            .min(self.header.len().saturating_sub(VISIBLE_COLS));
+        self.forget_removed_columns();
+    }
+
+    /// Drops a sort or range filter on a column that no longer exists.
+    fn forget_removed_columns(&mut self) {
+        self.clamp_cursor();
+        if self.sort_col.is_some_and(|col| col >= self.header.len()) {
+            self.sort_col = None;
+            self.resort();
+        }
+        if self
+            .range
+            .as_ref()
+            .is_some_and(|r| r.col >= self.header.len())
+        {
+            self.range = None;
+        }
+    }
+
+    /// Filters the loaded rows to a numeric range on the cursor column. An
+    /// empty input removes the filter.
+    fn set_range(&mut self, input: &str) {
+        if input.is_empty() {
+            self.range = None;
+        } else {
+            match parse_range(input) {
+                Ok((min, max)) => {
+                    self.range = Some(RangeFilter {
+                        col: self.cursor_col,
+                        min,
+                        max,
+                    })
+                }
+                Err(e) => {
+                    self.message = Some(format!("Range error: {}", e));
+                    return;
+                }
+            }
+        }
+        self.table_state.select(Some(0));
+    }
+
+    fn open_prompt(&mut self, kind: PromptKind, initial: &str) {
+        let mut input = TextArea::default();
+        input.insert_str(initial);
+        self.prompt = Some(Prompt { kind, input });
+    }
+
+    fn null_text(&self) -> &str {
+        self.options.null_text.as_deref().unwrap_or(NULL_SENTINEL)
+    }
+
+    /// A loaded row as it is drawn, with nulls shown as `--null-text`.
+    fn display_row(&self, row: usize) -> Vec<String> {
+        self.current_rows[row]
+            .iter()
+            .zip(&self.current_nulls[row])
+            .map(|(cell, &null)| {
+                if null {
+                    self.null_text().to_string()
+                } else {
+                    cell.clone()
+                }
+            })
+            .collect()
+    }
+
+    /// Writes the view as it is drawn: the shown rows in their sorted,
+    /// deduped and range-filtered order, and the visible columns including a
+    /// frozen first column.
+    fn export_markdown(&mut self, output_path: &str) {
+        let cols = self.visible_cols();
+        let header: Vec<String> = cols.iter().map(|&c| self.header[c].clone()).collect();
+        let rows: Vec<Vec<String>> = self
+            .shown_rows()
+            .into_iter()
+            .map(|i| {
+                let row = self.display_row(i);
+                cols.iter().map(|&c| row[c].clone()).collect()
+            })
+            .collect();
+
+        self.message = match std::fs::write(output_path, markdown_table(&header, &rows)) {
+            Ok(()) => Some(format!("Wrote {} rows to {}", rows.len(), output_path)),
+            Err(e) => Some(format!("Markdown export error: {}", e)),
+        };
+    }
+
+    fn handle_prompt_key(&mut self, key: KeyEvent) {
+        let Some(prompt) = &mut self.prompt else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Enter => {
+                if let Some(prompt) = self.prompt.take() {
+                    let input = prompt.input.lines().join("");
+                    match prompt.kind {
+                        PromptKind::ComputedColumn => self.add_computed_column(input.trim()),
+                        PromptKind::Mask => self.set_mask(input.trim()),
+                        PromptKind::GotoRow => self.goto_row(input.trim()),
+                        PromptKind::Search => self.start_search(&input),
+                        PromptKind::Range => self.set_range(input.trim()),
+                        PromptKind::ExportMarkdown => self.export_markdown(input.trim()),
+                    }
+                }
+            }
+            _ => {
+                prompt.input.input(key);
+            }
+        }
+    }
+
+    /// Reopens the file from scratch, staying on the same batch and columns
+    /// where the new file still has them.
+    fn reload(&mut self) {
+        let mut fresh = match App::new(self.file_path.clone(), self.options.clone()) {
+            Ok(app) => app,
+            Err(e) => {
+                self.message = Some(format!("Reload error: {}", e));
+                return;
+            }
+        };
+
+        let batch_idx = self
+            .current_batch_idx
+            .min(fresh.total_batches.saturating_sub(1));
+        if batch_idx > 0 {
+            if let Err(e) = fresh.load_batch(batch_idx) {
+                fresh.message = Some(format!("Reload error: {}", e));
+            }
+        }
+        let total_cols = fresh.current_rows.first().map_or(0, |r| r.len());
+        fresh.col_offset = self.col_offset.min(total_cols.saturating_sub(VISIBLE_COLS));
+        fresh.freeze_first = self.freeze_first;
+        fresh.show_detail = self.show_detail;
+        fresh.cursor_col = self.cursor_col;
+        fresh.clamp_cursor();
+        fresh.message.get_or_insert_with(|| "Reloaded".to_string());
+
+        *self = fresh;
+    }
+
+    fn goto_row(&mut self, input: &str) {
+        match input.replace(',', "").parse() {
+            Ok(row) => self.select_row(row),
+            Err(_) => self.message = Some(format!("Not a row number: '{}'", input)),
+        }
+    }
+
+    /// Loads the batch holding a zero-based row number and selects it.
+    /// Numbers past the end land on the last row.
+    fn select_row(&mut self, row: usize) {
+        if self.total_rows == 0 {
+            return;
+        }
+
+        let row = row.min(self.total_rows - 1);
+        let batch_idx = row / self.batch_size;
+        if batch_idx != self.current_batch_idx {
+            if let Err(e) = self.load_batch(batch_idx) {
+                self.message = Some(format!("Error loading batch: {}", e));
+                return;
+            }
+            if self.current_batch_idx != batch_idx {
+                return;
+            }
+        }
+
+        // With the dedupe view on, the row may be hidden; land on the shown
+        // row that stands for it.
+        let in_batch = row % self.batch_size;
+        let shown = self.shown_rows();
+        let position = shown
+            .iter()
+            .position(|&i| i == in_batch)
+            .or_else(|| shown.iter().rposition(|&i| i <= in_batch))
+            .unwrap_or(0);
+        self.table_state.select(Some(position));
+    }
+
+    fn start_search(&mut self, input: &str) {
+        if input.is_empty() {
+            self.search = None;
+            return;
+        }
+        self.search = Some(input.to_lowercase());
+        self.find_match(true);
+    }
+
+    /// Moves the selection to the next (or previous) shown row of the loaded
+    /// batch containing the search text, wrapping around at either end.
+    fn find_match(&mut self, forward: bool) {
+        let Some(needle) = &self.search else {
+            self.message = Some("No search; press / to search".to_string());
+            return;
+        };
+
+        let shown = self.shown_rows();
+        if shown.is_empty() {
+            return;
+        }
+        let selected = self.table_state.selected().unwrap_or(0);
+        let found = (1..=shown.len())
+            .map(|step| {
+                if forward {
+                    (selected + step) % shown.len()
+                } else {
+                    (selected + shown.len() - step) % shown.len()
+                }
+            })
+            .find(|&pos| row_matches(&self.current_rows[shown[pos]], needle));
+
+        match found {
+            Some(pos) => self.table_state.select(Some(pos)),
+            None => self.message = Some(format!("No match for '{}' in this batch", needle)),
+        }
+    }
+
+    /// Moves the selection and the column cursor to the next (or previous)
+    /// cell containing the search text, reading the shown rows left to right.
+    /// The cursor move scrolls a far-right match into view.
+    fn find_cell_match(&mut self, forward: bool) {
+        let Some(needle) = self.search.clone() else {
+            self.message = Some("No search; press / to search".to_string());
+            return;
+        };
+
+        let shown = self.shown_rows();
+        let cols = self.current_rows.first().map_or(0, |r| r.len());
+        let cells = shown.len() * cols;
+        if cells == 0 {
+            return;
+        }
+        let here = (self.table_state.selected().unwrap_or(0) * cols + self.cursor_col) % cells;
+        let found = (1..=cells)
+            .map(|step| {
+                if forward {
+                    (here + step) % cells
+                } else {
+                    (here + cells - step) % cells
+                }
+            })
+            .find(|&cell| {
+                self.current_rows[shown[cell / cols]][cell % cols]
+                    .to_lowercase()
+                    .contains(&needle)
+            });
+
+        match found {
+            Some(cell) => {
+                self.table_state.select(Some(cell / cols));
+                self.move_cursor(cell % cols);
+            }
+            None => self.message = Some(format!("No match for '{}' in this batch", needle)),
+        }
+    }
+
+    /// Keeps the selected row inside the loaded batch, which may be shorter
+    /// than the previous one (the final batch usually is).
+    fn clamp_selection(&mut self) {
+        if self.current_rows.is_empty() {
+            self.table_state.select(None);
+            return;
+        }
+
+        let last_row = self.current_rows.len() - 1;
+        let selected = self.table_state.selected().unwrap_or(0).min(last_row);
+        self.table_state.select(Some(selected));
+    }
+
     fn load_next_batch(&mut self) {
         if self.current_batch_idx + 1 < self.total_batches {
             if let Err(e) = self.load_batch(self.current_batch_idx + 1) {
@@ -91,6 +1154,29 @@ impl App {
         }
     }
 
+    fn load_first_batch(&mut self) {
+        if let Err(e) = self.load_batch(0) {
+            self.message = Some(format!("Error loading batch: {}", e));
+            return;
+        }
+        if self.current_batch_idx == 0 {
+            self.table_state.select(Some(0));
+        }
+    }
+
+    /// Loads the final batch and selects its last shown row.
+    fn load_last_batch(&mut self) {
+        let last = self.total_batches.saturating_sub(1);
+        if let Err(e) = self.load_batch(last) {
+            self.message = Some(format!("Error loading batch: {}", e));
+            return;
+        }
+        if self.current_batch_idx == last {
+            let shown = self.shown_rows().len();
+            self.table_state.select(shown.checked_sub(1));
+        }
+    }
+
     fn run(mut self, mut terminal: DefaultTerminal) -> Result<(), Box<dyn std::error::Error>> {
         loop {
             terminal.draw(|f| self.draw(f))?;
@@ -100,13 +1186,85 @@ impl App {
                     continue;
                 }
 
+                if self.prompt.is_some() {
+                    self.handle_prompt_key(key);
+                    continue;
+                }
+                if self.show_detail {
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('v') => {
+                            self.show_detail = false
+                        }
+                        KeyCode::Up => self.detail_scroll = self.detail_scroll.saturating_sub(1),
+                        KeyCode::Down => self.detail_scroll = self.detail_scroll.saturating_add(1),
+                        KeyCode::Char('o') => terminal = self.suspend_for_pager(),
+                        _ => {}
+                    }
+                    continue;
+                }
+                if self.show_cell {
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Char('c') => self.show_cell = false,
+                        KeyCode::Up => self.cell_scroll = self.cell_scroll.saturating_sub(1),
+                        KeyCode::Down => self.cell_scroll = self.cell_scroll.saturating_add(1),
+                        KeyCode::Char('o') => terminal = self.suspend_for_pager(),
+                        _ => {}
+                    }
+                    continue;
+                }
+                self.message = None;
+
                 match key.code {
                     KeyCode::Up => self.table_state.select_previous(),
                     KeyCode::Down => self.table_state.select_next(),
                     KeyCode::PageDown => self.load_next_batch(),
                     KeyCode::PageUp => self.load_previous_batch(),
-                    KeyCode::Left => self.scroll_left(),
-                    KeyCode::Right => self.scroll_right(),
+                    KeyCode::Home => self.load_first_batch(),
+                    KeyCode::End => self.load_last_batch(),
+                    KeyCode::Left if key.modifiers.contains(event::KeyModifiers::SHIFT) => {
+                        self.page_left()
+                    }
+                    KeyCode::Right if key.modifiers.contains(event::KeyModifiers::SHIFT) => {
+                        self.page_right()
+                    }
+                    KeyCode::Char('H') => self.page_left(),
+                    KeyCode::Char('L') => self.page_right(),
+                    KeyCode::Left => self.cursor_left(),
+                    KeyCode::Right => self.cursor_right(),
+                    KeyCode::Char('0') => self.move_cursor(0),
+                    KeyCode::Char('$') => self.scroll_to_last_cols(),
+                    KeyCode::Char('w') => self.fit_visible_widths(),
+                    KeyCode::Char('W') => self.col_widths.clear(),
+                    KeyCode::Char('i') => self.show_ordinals = !self.show_ordinals,
+                    KeyCode::Char('p') => {
+                        self.freeze_first = !self.freeze_first;
+                        self.clamp_cursor();
+                    }
+                    KeyCode::Char('n') => self.show_null_footer = !self.show_null_footer,
+                    KeyCode::Char('d') => self.toggle_dedupe(),
+                    KeyCode::Char('s') => self.toggle_sort(),
+                    KeyCode::Char('N') => self.toggle_nulls_first(),
+                    KeyCode::Char('R') => self.open_prompt(PromptKind::Range, ""),
+                    KeyCode::Enter | KeyCode::Char('v') => {
+                        self.show_detail = true;
+                        self.detail_scroll = 0;
+                    }
+                    KeyCode::Char('c') => {
+                        self.show_cell = true;
+                        self.cell_scroll = 0;
+                    }
+                    KeyCode::Char('=') => self.open_prompt(PromptKind::ComputedColumn, ""),
+                    KeyCode::Char('m') => self.open_prompt(PromptKind::ExportMarkdown, "view.md"),
+                    KeyCode::Char('X') => self.clear_computed_columns(),
+                    KeyCode::Char('b') => self.open_prompt(PromptKind::Mask, ""),
+                    KeyCode::Char('g') => self.open_prompt(PromptKind::GotoRow, ""),
+                    KeyCode::Char('/') => self.open_prompt(PromptKind::Search, ""),
+                    KeyCode::Char('f') => self.find_match(true),
+                    KeyCode::Char('F') => self.find_match(false),
+                    KeyCode::Tab => self.find_cell_match(true),
+                    KeyCode::BackTab => self.find_cell_match(false),
+                    KeyCode::Char('B') => self.remove_mask(),
+                    KeyCode::Char('r') => self.reload(),
                     KeyCode::Char('q') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
                         return Ok(());
                     }
@@ -117,21 +1275,197 @@ impl App {
         }
     }
 
-    fn scroll_left(&mut self) {
-        if self.col_offset > 0 {
-            self.col_offset -= 1;
+    /// Puts the column cursor on `col`, scrolling just far enough to keep it
+    /// on screen. A frozen first column is always on screen.
+    fn move_cursor(&mut self, col: usize) {
+        let total_cols = self.current_rows.first().map_or(0, |r| r.len());
+        self.cursor_col = col.min(total_cols.saturating_sub(1));
+        if self.freeze_first && self.cursor_col == 0 {
+            return;
+        }
+        if self.cursor_col < self.col_offset {
+            self.col_offset = self.cursor_col;
+        } else if self.cursor_col >= self.col_offset + VISIBLE_COLS {
+            self.col_offset = self.cursor_col + 1 - VISIBLE_COLS;
         }
     }
 
-    fn scroll_right(&mut self) {
-        if self.current_rows.is_empty() {
+    /// Pulls the cursor back into the window after the window or the
+    /// columns changed under it.
+    fn clamp_cursor(&mut self) {
+        if self.freeze_first && self.cursor_col == 0 {
             return;
         }
+        let (start, end) = self.visible_range();
+        self.cursor_col = self.cursor_col.min(end.saturating_sub(1)).max(start);
+    }
+
+    fn cursor_left(&mut self) {
+        self.move_cursor(self.cursor_col.saturating_sub(1));
+    }
+
+    fn cursor_right(&mut self) {
+        self.move_cursor(self.cursor_col + 1);
+    }
+
+    /// Moves the window to start at `offset`, keeping the cursor at the same
+    /// place on screen.
+    fn scroll_to(&mut self, offset: usize) {
+        if !(self.freeze_first && self.cursor_col == 0) {
+            self.cursor_col = (self.cursor_col + offset).saturating_sub(self.col_offset);
+        }
+        self.col_offset = offset;
+        self.clamp_cursor();
+    }
+
+    /// Moves a full screen of columns at a time, the sideways PageUp.
+    fn page_left(&mut self) {
+        self.scroll_to(self.col_offset.saturating_sub(VISIBLE_COLS));
+    }
+
+    fn page_right(&mut self) {
+        let total_cols = self.current_rows.first().map_or(0, |r| r.len());
+        self.scroll_to(
+            (self.col_offset + VISIBLE_COLS).min(total_cols.saturating_sub(VISIBLE_COLS)),
+        );
+    }
 
-        let total_cols = self.current_rows[0].len();
-        if self.col_offset + VISIBLE_COLS < total_cols {
-            self.col_offset += 1;
+    /// Hands the terminal to the pager while it runs, then takes it back.
+    fn suspend_for_pager(&mut self) -> DefaultTerminal {
+        ratatui::restore();
+        if let Err(e) = self.open_in_pager() {
+            self.message = Some(format!("Pager error: {}", e));
         }
+        ratatui::init()
+    }
+
+    /// Writes the cursor cell of the selected row to a temp file and opens
+    /// it in $PAGER, falling back to $EDITOR and then `less`, for values too
+    /// long to read in a popup.
+    fn open_in_pager(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let selected = self.table_state.selected().unwrap_or(0);
+        let Some(&row) = self.shown_rows().get(selected) else {
+            return Ok(());
+        };
+        let value = &self.display_row(row)[self.cursor_col];
+
+        let program = ["PAGER", "EDITOR"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|p| !p.trim().is_empty())
+            .unwrap_or_else(|| "less".to_string());
+        // The variable may carry arguments, e.g. "less -R".
+        let mut words = program.split_whitespace();
+        let command = words.next().unwrap_or("less");
+
+        let path = std::env::temp_dir().join(format!("pq-peak-cell-{}.txt", std::process::id()));
+        std::fs::write(&path, value)?;
+        let status = std::process::Command::new(command)
+            .args(words)
+            .arg(&path)
+            .status();
+        let _ = std::fs::remove_file(&path);
+
+        match status {
+            Ok(status) if status.success() => Ok(()),
+            Ok(status) => Err(format!("{} exited with {}", command, status).into()),
+            Err(e) => Err(format!("{}: {}", command, e).into()),
+        }
+    }
+
+    fn scroll_to_last_cols(&mut self) {
+        let total_cols = self.current_rows.first().map_or(0, |r| r.len());
+        self.move_cursor(total_cols.saturating_sub(1));
+    }
+
+    fn visible_range(&self) -> (usize, usize) {
+        let tc = self.current_rows.first().map_or(0, |r| r.len());
+        let start = self.col_offset;
+        (start, (start + VISIBLE_COLS).min(tc))
+    }
+
+    /// Columns drawn on screen, left to right. With the first column frozen
+    /// it stays leftmost once scrolled past.
+    fn visible_cols(&self) -> Vec<usize> {
+        let (start, end) = self.visible_range();
+        if self.freeze_first && start > 0 {
+            std::iter::once(0).chain(start..end).collect()
+        } else {
+            (start..end).collect()
+        }
+    }
+
+    fn toggle_dedupe(&mut self) {
+        self.dedupe = match self.dedupe {
+            DedupeMode::Off => DedupeMode::CursorColumn,
+            DedupeMode::CursorColumn => DedupeMode::AllColumns,
+            DedupeMode::AllColumns => DedupeMode::Off,
+        };
+        self.table_state.select(Some(0));
+    }
+
+    /// Indices into `current_rows` that are drawn, in sort order. Dedupe
+    /// compares each row with the last row kept, so rows hidden by the range
+    /// filter don't break a run.
+    fn shown_rows(&self) -> Vec<usize> {
+        let cursor = self.cursor_col;
+        let mut shown: Vec<usize> = Vec::new();
+        for &i in &self.row_order {
+            let row = &self.current_rows[i];
+            if self.range.as_ref().is_some_and(|r| !r.contains(row)) {
+                continue;
+            }
+            let nulls = &self.current_nulls[i];
+            let repeat = shown.last().is_some_and(|&kept| {
+                let (above, above_nulls) = (&self.current_rows[kept], &self.current_nulls[kept]);
+                match self.dedupe {
+                    DedupeMode::Off => false,
+                    DedupeMode::CursorColumn => {
+                        row[cursor] == above[cursor] && nulls[cursor] == above_nulls[cursor]
+                    }
+                    DedupeMode::AllColumns => row == above && nulls == above_nulls,
+                }
+            });
+            if !repeat {
+                shown.push(i);
+            }
+        }
+        shown
+    }
+
+    fn content_width(&self, col: usize) -> usize {
+        self.current_rows
+            .iter()
+            .map(|r| r[col].chars().count())
+            .chain(std::iter::once(self.header[col].chars().count()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn fit_visible_widths(&mut self) {
+        let (start, end) = self.visible_range();
+
+        for col in start..end {
+            let widest = self.content_width(col);
+            let width = (widest.min(MAX_FIT_WIDTH as usize) as u16).max(1);
+            self.col_widths.insert(col, width);
+        }
+    }
+
+    /// Splits the table's inner width across the visible columns in
+    /// proportion to their content, so files with few columns fill the screen.
+    fn proportional_widths(&self, cols: &[usize], area_width: u16) -> Vec<u16> {
+        let weights: Vec<usize> = cols.iter().map(|&c| self.content_width(c).max(1)).collect();
+        let total: usize = weights.iter().sum();
+
+        // Two border columns plus one space between each pair of columns.
+        let gaps = cols.len().saturating_sub(1);
+        let available = (area_width as usize).saturating_sub(2 + gaps);
+
+        weights
+            .iter()
+            .map(|w| ((available * w / total.max(1)) as u16).max(1))
+            .collect()
     }
 
     fn draw(&mut self, f: &mut Frame) {
@@ -142,31 +1476,102 @@ impl App {
         }
 
         let tc = self.current_rows[0].len();
-        let start = self.col_offset;
-        let end = (start + VISIBLE_COLS).min(tc);
+        let (start, end) = self.visible_range();
+        let cols = self.visible_cols();
 
+        // Types come from the loaded batch so `--cast` overrides show up;
+        // computed columns sit past the batch's own columns.
+        let batch_schema = self.current_batch.as_ref().map(|b| b.schema());
         let hdr = Row::new(
-            self.header[start..end]
-                .iter()
-                .map(|s| s.as_str())
+            cols.iter()
+                .map(|&c| {
+                    let name = &self.header[c];
+                    let mut label = if self.show_ordinals {
+                        format!("{}:{}", c, name)
+                    } else {
+                        name.clone()
+                    };
+                    if self.sort_col == Some(c) {
+                        label.push_str(if self.sort_desc { " ↓" } else { " ↑" });
+                    }
+                    let data_type = batch_schema
+                        .as_ref()
+                        .and_then(|s| s.fields().get(c).map(|f| f.data_type().to_string()))
+                        .unwrap_or_else(|| "computed".to_string());
+                    let cell = header_cell(label, &data_type, self.all_null_cols.contains(&c));
+                    // The cursor column is shown reversed so it's clear what s acts on.
+                    if c == self.cursor_col {
+                        cell.reversed()
+                    } else {
+                        cell
+                    }
+                })
                 .collect::<Vec<_>>(),
         )
-        .bold()
-        .height(1);
+        .height(2);
 
-        let visible_rows = self.current_rows.iter().map(|r| {
-            let slice = &r[start..end];
-            Row::new(slice.iter().map(String::as_str).collect::<Vec<_>>())
+        let shown = self.shown_rows();
+        // Null-ness comes from `current_nulls`, so a string value "NULL" is
+        // drawn as text and --null-text only replaces real nulls.
+        let null_text = self.options.null_text.as_deref().unwrap_or(NULL_SENTINEL);
+        let null_style = Style::new().fg(Color::DarkGray).italic().dim();
+        let visible_rows = shown.iter().map(|&i| {
+            let row = Row::new(
+                cols.iter()
+                    .map(|&c| {
+                        // Nulls are dimmed so missing data stands out in a scan.
+                        if self.current_nulls[i][c] {
+                            Cell::from(null_text).style(null_style)
+                        } else {
+                            Cell::from(self.current_rows[i][c].as_str())
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+            );
+            match &self.search {
+                Some(needle) if row_matches(&self.current_rows[i], needle) => row.fg(Color::Yellow),
+                _ => row,
+            }
         });
 
-        let widths = std::iter::repeat_n(12u16, end - start);
+        let widths: Vec<u16> = if self.options.fill_width {
+            self.proportional_widths(&cols, area.width)
+        } else {
+            // Size to the rows on screen; rows scrolled past don't widen columns.
+            let on_screen = shown
+                .iter()
+                .skip(self.table_state.offset())
+                .take(area.height as usize)
+                .map(|&i| &self.current_rows[i])
+                .collect::<Vec<_>>();
+            cols.iter()
+                .map(|&c| {
+                    self.col_widths.get(&c).copied().unwrap_or_else(|| {
+                        let rows = on_screen.iter().copied();
+                        content_widths(&self.header, rows, c, c + 1, MAX_AUTO_WIDTH)[0]
+                    })
+                })
+                .collect()
+        };
 
         let batch_start_row = self.current_batch_idx * self.batch_size;
         let current_batch_rows = self.current_rows.len();
         let batch_end_row = batch_start_row + current_batch_rows - 1;
 
+        let batch_memory = self
+            .current_batch
+            .as_ref()
+            .map_or(0, |b| b.get_array_memory_size());
+
+        let is_last_batch = self.current_batch_idx + 1 == self.total_batches;
+        let partial_note = if is_last_batch && current_batch_rows < self.batch_size {
+            format!(" (last, {} rows)", current_batch_rows)
+        } else {
+            String::new()
+        };
+
         let title = format!(
-            "Table | Cols {}–{}/{} | Rows {}–{}/{} | Batch {}/{} | [PgUp/PgDn: Batches | ←/→: Cols | ↑/↓: Rows | Esc: Quit]",
+            "Table | Cols {}–{}/{} | Rows {}–{}/{} | Batch {}/{}{} ({}) | [PgUp/PgDn: Batches | Home/End: First/Last batch | ←/→: Column cursor | H/L: Page cols | 0/$: First/Last cols | ↑/↓: Rows | g: Go to row | v/Enter: Record | c: Cell | /: Search | f/F: Next/Prev match | Tab/Shift+Tab: Next/Prev matching cell | w/W: Fit/Reset widths | i: Indexes | p: Freeze first col | n: Nulls | d: Dedupe | s: Sort batch | N: Nulls first/last | R: Range | =/X: Add/Clear computed | b/B: Mask on/off | m: Markdown | r: Reload | Esc: Quit]",
             start,
             end.saturating_sub(1),
             tc,
@@ -175,30 +1580,254 @@ impl App {
             self.total_rows,
             self.current_batch_idx + 1,
             self.total_batches,
+            partial_note,
+            format_bytes(batch_memory),
         );
 
-        let table = Table::new(visible_rows, widths)
+        let cursor = self.cursor_col;
+        let file_cols = self.derived_end() - self.computed.len();
+        let profile = match &self.source {
+            Source::Parquet(reader_metadata) if cursor < file_cols => Some(
+                self.profiles
+                    .entry(cursor)
+                    .or_insert_with(|| {
+                        let file_col = self.read_columns.as_ref().map_or(cursor, |c| c[cursor]);
+                        column_profile(reader_metadata, file_col, self.row_groups.as_deref())
+                            .unwrap_or_else(|e| {
+                                format!("No statistics for {}: {}", self.header[cursor], e)
+                            })
+                    })
+                    .clone(),
+            ),
+            _ => None,
+        };
+
+        let selected = self.table_state.selected().unwrap_or(0);
+        let selected_row = batch_start_row + shown.get(selected).copied().unwrap_or(0);
+        let position = format!(
+            "Row {} of {} — {:.1}%",
+            format_count(selected_row),
+            format_count(self.total_rows),
+            selected_row as f64 * 100.0 / self.total_rows.max(1) as f64
+        );
+        let dedupe_note = match self.dedupe {
+            DedupeMode::Off => None,
+            DedupeMode::CursorColumn => Some(format!(
+                "Dedupe on {}: {} of {} rows",
+                self.header[self.cursor_col],
+                shown.len(),
+                current_batch_rows
+            )),
+            DedupeMode::AllColumns => Some(format!(
+                "Dedupe on all columns: {} of {} rows",
+                shown.len(),
+                current_batch_rows
+            )),
+        };
+        let range_note = self.range.as_ref().map(|r| {
+            let non_numeric = self
+                .current_rows
+                .iter()
+                .filter(|row| row[r.col].parse::<f64>().is_err())
+                .count();
+            format!(
+                "{} in {}..{}: {} of {} rows ({} non-numeric excluded)",
+                self.header[r.col],
+                r.min,
+                r.max,
+                shown.len(),
+                current_batch_rows,
+                non_numeric
+            )
+        });
+        let sort_note = self.sort_col.map(|col| {
+            format!(
+                "Sorted by {} {}, nulls {} (loaded batch only)",
+                self.header[col],
+                if self.sort_desc { "↓" } else { "↑" },
+                if self.nulls_first { "first" } else { "last" }
+            )
+        });
+        let parquet = matches!(self.source, Source::Parquet(_));
+        let io_note = (self.options.debug_io && parquet).then(|| {
+            format!(
+                "IO: {} read, {} decompressed",
+                format_bytes(self.io_bytes.0 as usize),
+                format_bytes(self.io_bytes.1 as usize)
+            )
+        });
+        let footer_text: Vec<&str> = std::iter::once(&position)
+            .chain(profile.iter())
+            .chain(dedupe_note.iter())
+            .chain(range_note.iter())
+            .chain(sort_note.iter())
+            .chain(io_note.iter())
+            .chain(self.message.iter())
+            .chain(self.notices.iter())
+            .map(String::as_str)
+            .collect();
+
+        let block = Block::new()
+            .title(title)
+            .title_bottom(footer_text.join(" | "))
+            .borders(Borders::ALL)
+            .border_style(Style::default());
+
+        let mut table = Table::new(visible_rows, widths)
             .header(hdr)
+            .block(block)
+            .row_highlight_style(Style::new().underlined());
+
+        if self.show_null_footer {
+            let null_counts = cols.iter().map(|&c| {
+                let nulls = self.current_nulls.iter().filter(|n| n[c]).count();
+                format!("nulls: {}", nulls)
+            });
+            table = table.footer(Row::new(null_counts.collect::<Vec<_>>()).dim());
+        }
+
+        f.render_stateful_widget(table, area, &mut self.table_state);
+
+        if self.show_detail {
+            self.draw_detail(f, area);
+        }
+
+        if self.show_cell {
+            self.draw_cell(f, area);
+        }
+
+        if self.prompt.is_some() {
+            self.draw_prompt(f, area);
+        }
+    }
+
+    /// Shows the full, wrapped value of the cursor column in the selected row.
+    fn draw_cell(&self, f: &mut Frame, area: Rect) {
+        let selected = self.table_state.selected().unwrap_or(0);
+        let Some(&row) = self.shown_rows().get(selected) else {
+            return;
+        };
+        let cursor = self.cursor_col;
+
+        let popup_area = Rect {
+            x: area.width / 12,
+            y: area.height / 12,
+            width: area.width * 5 / 6,
+            height: area.height * 5 / 6,
+        };
+
+        let title = format!(
+            "{} at row {} (↑/↓: Scroll | o: Open in pager | c/Esc: Close)",
+            self.header[cursor],
+            format_count(self.current_batch_idx * self.batch_size + row)
+        );
+        let value = if self.current_nulls[row][cursor] {
+            self.null_text()
+        } else {
+            self.current_rows[row][cursor].as_str()
+        };
+        let popup = Paragraph::new(value)
             .block(
-                Block::new()
+                Block::default()
+                    .borders(Borders::ALL)
                     .title(title)
+                    .border_style(Style::default().fg(Color::Green)),
+            )
+            .wrap(Wrap { trim: false })
+            .scroll((self.cell_scroll, 0));
+
+        f.render_widget(Clear, popup_area);
+        f.render_widget(popup, popup_area);
+    }
+
+    /// Shows the selected row as one `column: value` line per column.
+    fn draw_detail(&self, f: &mut Frame, area: Rect) {
+        let selected = self.table_state.selected().unwrap_or(0);
+        let Some(&row) = self.shown_rows().get(selected) else {
+            return;
+        };
+
+        let lines = record_lines(&self.header, &self.display_row(row));
+        let height = (lines.len() as u16).saturating_add(2).min(area.height);
+        let popup_area = Rect {
+            x: area.width / 6,
+            y: (area.height - height) / 2,
+            width: area.width * 2 / 3,
+            height,
+        };
+
+        let title = format!(
+            "Row {} (↑/↓: Scroll | o: Open {} in pager | v/Enter/Esc: Close)",
+            format_count(self.current_batch_idx * self.batch_size + row),
+            self.header[self.cursor_col]
+        );
+        let popup = Paragraph::new(lines.join("\n"))
+            .block(
+                Block::default()
                     .borders(Borders::ALL)
-                    .border_style(Style::default()),
+                    .title(title)
+                    .border_style(Style::default().fg(Color::Green)),
             )
-            .row_highlight_style(Style::new().underlined());
+            .scroll((self.detail_scroll, 0));
 
-        f.render_stateful_widget(table, area, &mut self.table_state);
+        f.render_widget(Clear, popup_area);
+        f.render_widget(popup, popup_area);
+    }
+
+    fn draw_prompt(&mut self, f: &mut Frame, area: Rect) {
+        let Some(prompt) = &mut self.prompt else {
+            return;
+        };
+
+        let title = match prompt.kind {
+            PromptKind::ComputedColumn => {
+                "Computed column: name = expression (Enter to add, Esc to cancel)"
+            }
+            PromptKind::Mask => "Mask condition, e.g. amount > 100 (Enter to show, Esc to cancel)",
+            PromptKind::GotoRow => "Go to row (Enter to jump, Esc to cancel)",
+            PromptKind::Search => "Search this batch (Enter to find, Esc to cancel)",
+            PromptKind::Range => {
+                "Range on cursor column, e.g. 100..500 (Enter to filter, empty to clear)"
+            }
+            PromptKind::ExportMarkdown => {
+                "Export view as Markdown to (Enter to save, Esc to cancel)"
+            }
+        };
+        prompt.input.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(Color::Green)),
+        );
+
+        let popup_area = Rect {
+            x: area.width / 4,
+            y: (area.height / 2).saturating_sub(1),
+            width: area.width / 2,
+            height: 3.min(area.height),
+        };
+
+        f.render_widget(Clear, popup_area);
+        f.render_widget(&prompt.input, popup_area);
     }
 }
 
 pub fn build_table(
     file_path: PathBuf,
-    batch_size: usize,
+    options: PeakOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     color_eyre::install()?;
 
+    // Open the file before taking over the terminal, so a bad option or an
+    // unreadable file is reported as a plain error.
+    let select_row = options.select_row;
+    let mut app = App::new(file_path, options)?;
+    if let Some(row) = select_row {
+        app.select_row(row);
+    }
+
     let terminal = ratatui::init();
-    let app_result = App::new(file_path, batch_size)?.run(terminal);
+    let app_result = app.run(terminal);
     ratatui::restore();
 
     app_result
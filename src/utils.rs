@@ -1,6 +1,258 @@
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, ArrayRef, RecordBatch, StringArray},
+    datatypes::{DataType, Field, Schema},
+    error::ArrowError,
+};
+use parquet::{basic::Type, file::metadata::ParquetMetaData, schema::types::SchemaDescriptor};
+use ratatui::{
+    style::Stylize,
+    text::{Line, Text},
+    widgets::Cell,
+};
+
+use crate::peak::cell_text;
+
 pub fn validate_extension(path: &std::path::PathBuf) -> bool {
     if let Some(ext) = path.extension() {
-        return ext.eq("parquet") || ext.eq(".pqt");
+        let ext = ext.to_ascii_lowercase();
+        return ext.eq("parquet") || ext.eq("pqt");
     }
     false
 }
+
+/// CSV has no nested types, so list, struct and map columns are written as
+/// their display text. Nulls stay null.
+pub fn nested_as_text(batch: &RecordBatch) -> Result<RecordBatch, ArrowError> {
+    let schema = batch.schema();
+    let mut fields = Vec::new();
+    let mut columns = Vec::new();
+    for (field, col) in schema.fields().iter().zip(batch.columns()) {
+        if field.data_type().is_nested() {
+            let text: StringArray = (0..col.len())
+                .map(|i| col.is_valid(i).then(|| cell_text(col, i)))
+                .collect();
+            fields.push(Arc::new(Field::new(field.name(), DataType::Utf8, true)));
+            columns.push(Arc::new(text) as ArrayRef);
+        } else {
+            fields.push(field.clone());
+            columns.push(col.clone());
+        }
+    }
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+}
+
+/// INT96 timestamps (legacy Spark/Impala) are decoded by the Arrow reader as
+/// Timestamp(ns); name the affected columns so the conversion isn't silent.
+pub fn int96_notice(schema: &SchemaDescriptor) -> Option<String> {
+    let columns: Vec<String> = schema
+        .columns()
+        .iter()
+        .filter(|c| c.physical_type() == Type::INT96)
+        .map(|c| c.path().string())
+        .collect();
+
+    if columns.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "INT96 columns read as Timestamp(ns): {}",
+        columns.join(", ")
+    ))
+}
+
+/// Leaf columns whose recorded null counts add up to every row in the file,
+/// which usually points at a pipeline bug upstream. Columns with a missing
+/// null count in any row group are never reported.
+pub fn all_null_columns(metadata: &ParquetMetaData) -> Vec<String> {
+    let total_rows = metadata.file_metadata().num_rows();
+    if total_rows == 0 {
+        return Vec::new();
+    }
+
+    let columns = metadata.file_metadata().schema_descr().columns();
+    (0..columns.len())
+        .filter(|&idx| {
+            let nulls: Option<u64> = metadata
+                .row_groups()
+                .iter()
+                .map(|rg| rg.column(idx).statistics().and_then(|s| s.null_count_opt()))
+                .sum();
+            nulls == Some(total_rows as u64)
+        })
+        .map(|idx| columns[idx].path().string())
+        .collect()
+}
+
+/// One aligned `name  type` line per field, flagging non-nullable columns.
+pub fn describe_fields(schema: &Schema) -> Vec<String> {
+    let width = schema
+        .fields()
+        .iter()
+        .map(|f| f.name().chars().count())
+        .max()
+        .unwrap_or(0);
+
+    schema
+        .fields()
+        .iter()
+        .map(|f| {
+            let nullability = if f.is_nullable() { "" } else { " NOT NULL" };
+            format!(
+                "{:<width$}  {}{}",
+                f.name(),
+                f.data_type(),
+                nullability,
+                width = width
+            )
+        })
+        .collect()
+}
+
+/// One aligned `name: value` line per column of a single row.
+pub fn record_lines(header: &[String], row: &[String]) -> Vec<String> {
+    let width = header.iter().map(|h| h.chars().count()).max().unwrap_or(0);
+
+    header
+        .iter()
+        .zip(row)
+        .map(|(name, value)| {
+            format!(
+                "{:<width$}  {}",
+                format!("{}:", name),
+                value,
+                width = width + 1
+            )
+        })
+        .collect()
+}
+
+/// Column name in bold with its Arrow type dimmed on a second line. Columns
+/// that are null in every row get a dimmed name instead.
+pub fn header_cell(label: String, data_type: &str, all_null: bool) -> Cell<'static> {
+    let label = if all_null {
+        Line::from(label).dim()
+    } else {
+        Line::from(label).bold()
+    };
+    Cell::from(Text::from(vec![
+        label,
+        Line::from(data_type.to_string()).dim(),
+    ]))
+}
+
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Width of each column in `start..end`: the longest of its header and the
+/// given rows, kept between 1 and `max`.
+pub fn content_widths<'a>(
+    header: &[String],
+    rows: impl Iterator<Item = &'a Vec<String>>,
+    start: usize,
+    end: usize,
+    max: usize,
+) -> Vec<u16> {
+    let mut widths: Vec<usize> = header[start..end]
+        .iter()
+        .map(|h| h.chars().count())
+        .collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(&row[start..end]) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    widths.iter().map(|w| (*w).clamp(1, max) as u16).collect()
+}
+
+/// Current UTC time of day as `HH:MM:SS`.
+pub fn clock_time() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+        % 86_400;
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Groups digits in thousands, e.g. `1234567` -> `1,234,567`.
+pub fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut out = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out
+}
+
+/// Renders rows as a GitHub-flavored Markdown table.
+pub fn markdown_table(header: &[String], rows: &[Vec<String>]) -> String {
+    let escape = |cell: &str| cell.replace('|', "\\|").replace('\n', " ");
+
+    let mut out = String::new();
+    out.push_str(&format!(
+        "| {} |\n",
+        header
+            .iter()
+            .map(|h| escape(h))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    ));
+    out.push_str(&format!("|{}\n", " --- |".repeat(header.len())));
+    for row in rows {
+        out.push_str(&format!(
+            "| {} |\n",
+            row.iter()
+                .map(|c| escape(c))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        ));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::validate_extension;
+
+    #[test]
+    fn accepts_parquet_extensions() {
+        assert!(validate_extension(&PathBuf::from("foo.parquet")));
+        assert!(validate_extension(&PathBuf::from("foo.pqt")));
+    }
+
+    #[test]
+    fn rejects_other_extensions() {
+        assert!(!validate_extension(&PathBuf::from("foo.csv")));
+        assert!(!validate_extension(&PathBuf::from("foo")));
+    }
+
+    #[test]
+    fn ignores_extension_case() {
+        assert!(validate_extension(&PathBuf::from("DATA.PARQUET")));
+        assert!(validate_extension(&PathBuf::from("export.Parquet")));
+        assert!(validate_extension(&PathBuf::from("x.PQT")));
+    }
+}
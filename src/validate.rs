@@ -0,0 +1,130 @@
+use std::path::PathBuf;
+
+use arrow::{
+    array::{ArrayRef, RecordBatchReader},
+    compute::SortOptions,
+    row::{OwnedRow, RowConverter, SortField},
+};
+use parquet::{arrow::ProjectionMask, format::SortingColumn};
+
+use crate::inspect::open_builder;
+
+/// Scans one row group's declared sort columns and returns the position
+/// (within the row group) of the first row that sorts before its
+/// predecessor.
+fn first_unsorted_row(
+    path: &PathBuf,
+    row_group: usize,
+    sorting: &[SortingColumn],
+) -> Result<Option<usize>, Box<dyn std::error::Error>> {
+    let builder = open_builder(path)?;
+    let leaves: Vec<usize> = sorting.iter().map(|s| s.column_idx as usize).collect();
+    let names: Vec<String> = leaves
+        .iter()
+        .map(|&idx| builder.parquet_schema().column(idx).path().string())
+        .collect();
+    let mask = ProjectionMask::leaves(builder.parquet_schema(), leaves);
+    let reader = builder
+        .with_row_groups(vec![row_group])
+        .with_projection(mask)
+        .build()?;
+
+    let schema = reader.schema();
+    let mut indices = Vec::new();
+    let mut fields = Vec::new();
+    for (name, sort) in names.iter().zip(sorting) {
+        let idx = schema.index_of(name).map_err(|_| {
+            format!(
+                "sort column '{}' is nested; only top-level columns can be checked",
+                name
+            )
+        })?;
+        let options = SortOptions {
+            descending: sort.descending,
+            nulls_first: sort.nulls_first,
+        };
+        indices.push(idx);
+        fields.push(SortField::new_with_options(
+            schema.field(idx).data_type().clone(),
+            options,
+        ));
+    }
+    let converter = RowConverter::new(fields)?;
+
+    // Only the last row of each batch is kept, to compare across batches.
+    let mut previous: Option<OwnedRow> = None;
+    let mut offset = 0;
+    for batch in reader {
+        let batch = batch?;
+        let columns: Vec<ArrayRef> = indices.iter().map(|&i| batch.column(i).clone()).collect();
+        let rows = converter.convert_columns(&columns)?;
+
+        for i in 0..rows.num_rows() {
+            let row = rows.row(i);
+            let out_of_order = match i {
+                0 => previous.as_ref().is_some_and(|prev| row < prev.row()),
+                _ => row < rows.row(i - 1),
+            };
+            if out_of_order {
+                return Ok(Some(offset + i));
+            }
+        }
+
+        if let Some(last) = rows.num_rows().checked_sub(1) {
+            previous = Some(rows.row(last).owned());
+        }
+        offset += rows.num_rows();
+    }
+
+    Ok(None)
+}
+
+/// Problems are returned as errors; with `quiet` the "ok" lines are left
+/// out and the exit code is the only result.
+pub fn validate(
+    path: &PathBuf,
+    check_sort: bool,
+    quiet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let builder = open_builder(path)?;
+    let metadata = builder.metadata().clone();
+    if !quiet {
+        println!(
+            "footer: ok ({} rows in {} row groups)",
+            metadata.file_metadata().num_rows(),
+            metadata.num_row_groups()
+        );
+    }
+
+    if !check_sort {
+        return Ok(());
+    }
+
+    let mut checked = 0;
+    let mut row_offset = 0;
+    for (idx, rg) in metadata.row_groups().iter().enumerate() {
+        if let Some(sorting) = rg.sorting_columns().filter(|s| !s.is_empty()) {
+            if let Some(row) = first_unsorted_row(path, idx, sorting)? {
+                return Err(format!(
+                    "row group {} is not sorted as declared: row {} (row {} of the file) sorts before the row above it",
+                    idx,
+                    row,
+                    row_offset + row
+                )
+                .into());
+            }
+            checked += 1;
+        }
+        row_offset += rg.num_rows() as usize;
+    }
+
+    if !quiet {
+        if checked == 0 {
+            println!("sort: no row groups declare sorting columns");
+        } else {
+            println!("sort: ok ({} row groups checked)", checked);
+        }
+    }
+
+    Ok(())
+}